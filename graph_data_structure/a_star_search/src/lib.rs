@@ -0,0 +1,496 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+pub type NodeId = usize;
+pub type Cost = u64;
+
+/// A `(row, col)` position on a grid or chess board
+pub type Position = (usize, usize);
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<(NodeId, Cost)>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // Use this method to add directed edge u --> v
+    // with the default cost of 1
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_weighted_directed_edge(u, v, 1);
+    }
+
+    // add a bi-directional edge u <--> v
+    // with the default cost of 1
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_weighted_undirected_edge(u, v, 1);
+    }
+
+    // Use this method to add directed edge u --(cost)--> v
+    pub fn add_weighted_directed_edge(
+        &mut self,
+        u: NodeId,
+        v: NodeId,
+        cost: Cost,
+    ) {
+        self.adjacency_list.entry(u).or_default().push((v, cost));
+        self.add_node(v);
+    }
+
+    // add a bi-directional edge u <--(cost)--> v
+    pub fn add_weighted_undirected_edge(
+        &mut self,
+        u: NodeId,
+        v: NodeId,
+        cost: Cost,
+    ) {
+        self.add_weighted_directed_edge(u, v, cost);
+        self.add_weighted_directed_edge(v, u, cost);
+    }
+
+    fn neighbours(&self, node: &NodeId) -> Vec<(NodeId, Cost)> {
+        self.adjacency_list.get(node).cloned().unwrap_or_default()
+    }
+}
+
+/// An estimate of the remaining cost from `node` to the goal.
+///
+/// For A* to return an optimal path the estimate must never
+/// be larger than the real remaining cost (admissible).
+pub trait Heuristic<N> {
+    fn estimate(&self, node: &N) -> Cost;
+}
+
+/// Any closure `Fn(&N) -> Cost` can be used as a heuristic
+impl<N, F> Heuristic<N> for F
+where
+    F: Fn(&N) -> Cost,
+{
+    fn estimate(&self, node: &N) -> Cost {
+        self(node)
+    }
+}
+
+/// Always estimates 0, which turns A* into Dijkstra.
+/// Use this when there is nothing better to go on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Zero;
+
+impl<N> Heuristic<N> for Zero {
+    fn estimate(&self, _node: &N) -> Cost {
+        0
+    }
+}
+
+/// `|dr| + |dc|`, for grids with up, down, left and right moves
+#[derive(Clone, Copy, Debug)]
+pub struct Manhattan(pub Position);
+
+impl Heuristic<Position> for Manhattan {
+    fn estimate(&self, node: &Position) -> Cost {
+        let (dr, dc) = deltas(*node, self.0);
+        (dr + dc) as Cost
+    }
+}
+
+/// `max(|dr|, |dc|)`, for grids that also allow diagonal moves
+#[derive(Clone, Copy, Debug)]
+pub struct Chebyshev(pub Position);
+
+impl Heuristic<Position> for Chebyshev {
+    fn estimate(&self, node: &Position) -> Cost {
+        let (dr, dc) = deltas(*node, self.0);
+        dr.max(dc) as Cost
+    }
+}
+
+/// The straight line distance rounded down, for grids with up,
+/// down, left and right moves. Not admissible with diagonal
+/// moves: from (0, 0) to (3, 3) it says 4 where three moves
+/// do, use `Chebyshev` there.
+#[derive(Clone, Copy, Debug)]
+pub struct Euclidean(pub Position);
+
+impl Heuristic<Position> for Euclidean {
+    fn estimate(&self, node: &Position) -> Cost {
+        let (dr, dc) = deltas(*node, self.0);
+        ((dr * dr + dc * dc) as f64).sqrt().floor() as Cost
+    }
+}
+
+/// A lower bound on the number of knight moves.
+///
+/// A single knight move changes the manhattan distance by at
+/// most 3 and the chebyshev distance by at most 2. Every move
+/// also flips the colour of the square the knight stands on,
+/// so the number of moves has the same parity as `dr + dc`.
+#[derive(Clone, Copy, Debug)]
+pub struct KnightMoves(pub Position);
+
+impl Heuristic<Position> for KnightMoves {
+    fn estimate(&self, node: &Position) -> Cost {
+        let (dr, dc) = deltas(*node, self.0);
+        let bound = (dr + dc).div_ceil(3).max(dr.max(dc).div_ceil(2));
+        if bound % 2 == (dr + dc) % 2 {
+            bound as Cost
+        } else {
+            bound as Cost + 1
+        }
+    }
+}
+
+/// Takes the smallest estimate of a list of heuristics.
+/// Handy when any one of several goals will do.
+#[derive(Clone, Debug)]
+pub struct Nearest<H>(pub Vec<H>);
+
+impl<N, H: Heuristic<N>> Heuristic<N> for Nearest<H> {
+    fn estimate(&self, node: &N) -> Cost {
+        self.0.iter().map(|h| h.estimate(node)).min().unwrap_or(0)
+    }
+}
+
+fn deltas(a: Position, b: Position) -> (usize, usize) {
+    (a.0.abs_diff(b.0), a.1.abs_diff(b.1))
+}
+
+/// Generic A* search.
+///
+/// `neighbours` returns the nodes reachable in one step from a
+/// node together with the (non-negative) cost of the step and
+/// `is_goal` tells the search when to stop. Returns the path
+/// (start and goal included) and its total cost, or `None`
+/// if no goal can be reached.
+pub fn a_star<N, F, G, H>(
+    start: N,
+    mut neighbours: F,
+    mut is_goal: G,
+    heuristic: &H,
+) -> Option<(Vec<N>, Cost)>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> Vec<(N, Cost)>,
+    G: FnMut(&N) -> bool,
+    H: Heuristic<N>,
+{
+    // Nodes are discovered lazily so we give each one an
+    // index the first time we see it and keep the book
+    // keeping in plain vectors.
+    let mut index: HashMap<N, usize> = HashMap::new();
+    let mut nodes = vec![start.clone()];
+    let mut best_cost: Vec<Cost> = vec![0];
+    let mut came_from: Vec<Option<usize>> = vec![None];
+    index.insert(start.clone(), 0);
+
+    // ordered by (estimated total cost, cost so far, index)
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((heuristic.estimate(&start), 0, 0)));
+
+    while let Some(Reverse((_, cost, current))) = open.pop() {
+        if cost > best_cost[current] {
+            // stale entry, a cheaper way in was found later
+            continue;
+        }
+
+        if is_goal(&nodes[current]) {
+            let mut path = vec![nodes[current].clone()];
+            let mut step = current;
+            while let Some(previous) = came_from[step] {
+                path.push(nodes[previous].clone());
+                step = previous;
+            }
+            path.reverse();
+            return Some((path, cost));
+        }
+
+        for (neighbour, step_cost) in neighbours(&nodes[current]) {
+            let candidate = cost + step_cost;
+            let i = match index.get(&neighbour) {
+                Some(i) if candidate >= best_cost[*i] => continue,
+                Some(i) => *i,
+                None => {
+                    nodes.push(neighbour.clone());
+                    best_cost.push(candidate);
+                    came_from.push(None);
+                    index.insert(neighbour.clone(), nodes.len() - 1);
+                    nodes.len() - 1
+                }
+            };
+            best_cost[i] = candidate;
+            came_from[i] = Some(current);
+            let estimate = candidate + heuristic.estimate(&neighbour);
+            open.push(Reverse((estimate, candidate, i)));
+        }
+    }
+
+    None
+}
+
+/// A* between two nodes of a weighted graph
+pub fn graph_a_star<H: Heuristic<NodeId>>(
+    graph: &Graph,
+    start: NodeId,
+    goal: NodeId,
+    heuristic: &H,
+) -> Option<(Vec<NodeId>, Cost)> {
+    a_star(
+        start,
+        |node| graph.neighbours(node),
+        |node| *node == goal,
+        heuristic,
+    )
+}
+
+/// Which moves are allowed on a character grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridMoves {
+    /// up, down, left and right
+    Four,
+    /// the four above plus the diagonals
+    Eight,
+}
+
+/// Every in bounds, non wall ('X') neighbour of `pos`.
+/// Each move costs 1.
+pub fn grid_neighbours<const N: usize>(
+    grid: &[[char; N]],
+    pos: Position,
+    moves: GridMoves,
+) -> Vec<(Position, Cost)> {
+    let deltas: &[(isize, isize)] = match moves {
+        GridMoves::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+        GridMoves::Eight => &[
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ],
+    };
+
+    let mut neighbours = vec![];
+    for (delta_row, delta_col) in deltas {
+        let row = pos.0.checked_add_signed(*delta_row);
+        let col = pos.1.checked_add_signed(*delta_col);
+        if let (Some(row), Some(col)) = (row, col) {
+            if row < grid.len() && col < N && grid[row][col] != 'X' {
+                neighbours.push(((row, col), 1));
+            }
+        }
+    }
+    neighbours
+}
+
+/// Shortest path between two cells of a grid where 'X's are
+/// walls, using the manhattan (four moves) or chebyshev
+/// (eight moves) heuristic.
+pub fn grid_a_star<const N: usize>(
+    grid: &[[char; N]],
+    start: Position,
+    goal: Position,
+    moves: GridMoves,
+) -> Option<(Vec<Position>, Cost)> {
+    let neighbours = |pos: &Position| grid_neighbours(grid, *pos, moves);
+    let is_goal = |pos: &Position| *pos == goal;
+    match moves {
+        GridMoves::Four => {
+            a_star(start, neighbours, is_goal, &Manhattan(goal))
+        }
+        GridMoves::Eight => {
+            a_star(start, neighbours, is_goal, &Chebyshev(goal))
+        }
+    }
+}
+
+/// Same question as `closest_carrot` but answered with A*
+/// aiming for the nearest `target` cell and returning the path
+/// as well.
+pub fn closest_target<const N: usize>(
+    grid: &[[char; N]],
+    start: Position,
+    target: char,
+) -> Option<(Vec<Position>, Cost)> {
+    let mut targets = vec![];
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            if *cell == target {
+                targets.push(Manhattan((row, col)));
+            }
+        }
+    }
+
+    a_star(
+        start,
+        |pos| grid_neighbours(grid, *pos, GridMoves::Four),
+        |pos| grid[pos.0][pos.1] == target,
+        &Nearest(targets),
+    )
+}
+
+/// Same question as `knight_attack` on an `n` x `n` board,
+/// answered with A* and the knight move lower bound.
+pub fn knight_path(
+    n: usize,
+    knight: Position,
+    pawn: Position,
+) -> Option<(Vec<Position>, Cost)> {
+    let moves = |pos: &Position| {
+        let jumps = [(2, 1), (2, -1), (-2, 1), (-2, -1)];
+        let mut positions = vec![];
+        for (a, b) in jumps {
+            for (delta_row, delta_col) in [(a, b), (b, a)] {
+                let row = pos.0.checked_add_signed(delta_row);
+                let col = pos.1.checked_add_signed(delta_col);
+                if let (Some(row), Some(col)) = (row, col) {
+                    if row < n && col < n {
+                        positions.push(((row, col), 1));
+                    }
+                }
+            }
+        }
+        positions
+    };
+
+    a_star(knight, moves, |pos| *pos == pawn, &KnightMoves(pawn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_a_star_00() {
+        let mut graph = Graph::new();
+        graph.add_weighted_undirected_edge(0, 1, 7);
+        graph.add_weighted_undirected_edge(0, 2, 9);
+        graph.add_weighted_undirected_edge(0, 5, 14);
+        graph.add_weighted_undirected_edge(1, 2, 10);
+        graph.add_weighted_undirected_edge(1, 3, 15);
+        graph.add_weighted_undirected_edge(2, 3, 11);
+        graph.add_weighted_undirected_edge(2, 5, 2);
+        graph.add_weighted_undirected_edge(3, 4, 6);
+        graph.add_weighted_undirected_edge(4, 5, 9);
+        graph.add_node(6);
+        let result = graph_a_star(&graph, 0, 4, &Zero);
+        assert_eq!(result, Some((vec![0, 2, 5, 4], 20)));
+        let result = graph_a_star(&graph, 0, 0, &Zero);
+        assert_eq!(result, Some((vec![0], 0)));
+        let result = graph_a_star(&graph, 0, 6, &Zero);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_graph_a_star_01() {
+        // nodes laid out on a line, the id is the position
+        // 0 <--> 1 <--> 2 <--> 3
+        // ^                    ^
+        // |                    |
+        //  --------(5)---------
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_weighted_undirected_edge(0, 3, 5);
+        let heuristic = |node: &NodeId| 3u64.abs_diff(*node as u64);
+        let result = graph_a_star(&graph, 0, 3, &heuristic);
+        assert_eq!(result, Some((vec![0, 1, 2, 3], 3)));
+    }
+
+    #[test]
+    fn test_grid_a_star() {
+        let grid = [
+            ['O', 'O', 'O', 'O', 'O'],
+            ['O', 'X', 'O', 'O', 'O'],
+            ['O', 'X', 'X', 'O', 'O'],
+            ['O', 'X', 'C', 'O', 'O'],
+            ['O', 'X', 'X', 'O', 'O'],
+            ['C', 'O', 'O', 'O', 'O'],
+        ];
+        let (path, cost) =
+            grid_a_star(&grid, (1, 2), (3, 2), GridMoves::Four).unwrap();
+        assert_eq!(cost, 4);
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.first(), Some(&(1, 2)));
+        assert_eq!(path.last(), Some(&(3, 2)));
+
+        let (_, cost) =
+            grid_a_star(&grid, (0, 0), (5, 4), GridMoves::Eight).unwrap();
+        assert_eq!(cost, 6);
+        assert_eq!(grid_a_star(&grid, (0, 0), (1, 1), GridMoves::Four), None);
+    }
+
+    #[test]
+    fn test_closest_target() {
+        let grid = [
+            ['O', 'O', 'X', 'X', 'X'],
+            ['O', 'X', 'X', 'X', 'C'],
+            ['O', 'X', 'O', 'X', 'X'],
+            ['O', 'O', 'O', 'O', 'O'],
+            ['O', 'X', 'X', 'X', 'X'],
+            ['O', 'O', 'O', 'O', 'O'],
+            ['O', 'O', 'C', 'O', 'O'],
+            ['O', 'O', 'O', 'O', 'O'],
+        ];
+        let (path, cost) = closest_target(&grid, (3, 4), 'C').unwrap();
+        assert_eq!(cost, 9);
+        assert_eq!(path.last(), Some(&(6, 2)));
+
+        let grid = [
+            ['O', 'O', 'X', 'O', 'O'],
+            ['O', 'X', 'X', 'X', 'O'],
+            ['O', 'X', 'C', 'C', 'O'],
+        ];
+        let (path, cost) = closest_target(&grid, (1, 4), 'C').unwrap();
+        assert_eq!(cost, 2);
+        assert_eq!(path, vec![(1, 4), (2, 4), (2, 3)]);
+        assert_eq!(closest_target(&grid, (2, 0), 'C'), None);
+    }
+
+    #[test]
+    fn test_knight_path() {
+        let cases = [
+            (8, (1, 1), (2, 2), Some(2)),
+            (8, (1, 1), (2, 3), Some(1)),
+            (8, (0, 3), (4, 2), Some(3)),
+            (8, (0, 3), (5, 2), Some(4)),
+            (24, (4, 7), (19, 20), Some(10)),
+            (100, (21, 10), (0, 0), Some(11)),
+            (3, (0, 0), (1, 2), Some(1)),
+            (3, (0, 0), (1, 1), None),
+        ];
+        for (n, knight, pawn, expected) in cases {
+            let result = knight_path(n, knight, pawn);
+            assert_eq!(result.as_ref().map(|(_, cost)| *cost), expected);
+            if let Some((path, cost)) = result {
+                assert_eq!(path.len() as Cost, cost + 1);
+                assert_eq!(path.first(), Some(&knight));
+                assert_eq!(path.last(), Some(&pawn));
+            }
+        }
+    }
+
+    #[test]
+    fn test_heuristics() {
+        assert_eq!(Manhattan((0, 0)).estimate(&(3, 4)), 7);
+        assert_eq!(Chebyshev((0, 0)).estimate(&(3, 4)), 4);
+        assert_eq!(Euclidean((0, 0)).estimate(&(3, 4)), 5);
+        assert_eq!(KnightMoves((0, 0)).estimate(&(0, 0)), 0);
+        assert_eq!(KnightMoves((0, 0)).estimate(&(1, 2)), 1);
+        assert_eq!(KnightMoves((0, 0)).estimate(&(1, 1)), 2);
+        let nearest = Nearest(vec![Manhattan((0, 0)), Manhattan((5, 5))]);
+        assert_eq!(nearest.estimate(&(4, 4)), 2);
+    }
+}
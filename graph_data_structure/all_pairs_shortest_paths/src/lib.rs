@@ -0,0 +1,414 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+pub type NodeId = usize;
+pub type Weight = i64;
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<(NodeId, Weight)>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // Use this method to add directed edge u --> v
+    // with the default weight of 1
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_weighted_directed_edge(u, v, 1);
+    }
+
+    // add a bi-directional edge u <--> v
+    // with the default weight of 1
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_weighted_undirected_edge(u, v, 1);
+    }
+
+    // Use this method to add directed edge u --(w)--> v
+    pub fn add_weighted_directed_edge(
+        &mut self,
+        u: NodeId,
+        v: NodeId,
+        weight: Weight,
+    ) {
+        self.adjacency_list.entry(u).or_default().push((v, weight));
+        // make sure `v` is a node as well so that
+        // we never look up a missing adjacency entry
+        self.add_node(v);
+    }
+
+    // add a bi-directional edge u <--(w)--> v
+    pub fn add_weighted_undirected_edge(
+        &mut self,
+        u: NodeId,
+        v: NodeId,
+        weight: Weight,
+    ) {
+        self.add_weighted_directed_edge(u, v, weight);
+        self.add_weighted_directed_edge(v, u, weight);
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+}
+
+/// The result of an all-pairs shortest path computation.
+///
+/// Every node of the graph gets a row and a column in the
+/// matrix. `distance[i][j]` is `None` when there is no path
+/// from node `i` to node `j`. `predecessor[i][j]` is the node
+/// that comes right before `j` on a shortest path from `i`,
+/// which is all we need to rebuild the path itself.
+#[derive(Clone, Debug)]
+pub struct DistanceMatrix {
+    nodes: Vec<NodeId>,
+    index: HashMap<NodeId, usize>,
+    distance: Vec<Vec<Option<Weight>>>,
+    predecessor: Vec<Vec<Option<usize>>>,
+}
+
+impl DistanceMatrix {
+    fn new(nodes: Vec<NodeId>) -> Self {
+        let n = nodes.len();
+        let index = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let mut distance = vec![vec![None; n]; n];
+        for (i, row) in distance.iter_mut().enumerate() {
+            row[i] = Some(0);
+        }
+        Self { nodes, index, distance, predecessor: vec![vec![None; n]; n] }
+    }
+
+    /// The nodes covered by this matrix in ascending order
+    pub fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
+    /// Length of the shortest path from `u` to `v` or
+    /// `None` if `v` can't be reached from `u`
+    pub fn distance(&self, u: NodeId, v: NodeId) -> Option<Weight> {
+        let (i, j) = (*self.index.get(&u)?, *self.index.get(&v)?);
+        self.distance[i][j]
+    }
+
+    /// Rebuilds a shortest path from `u` to `v` (both ends
+    /// included) by walking the predecessors back from `v`.
+    pub fn path(&self, u: NodeId, v: NodeId) -> Option<Vec<NodeId>> {
+        let (i, mut j) = (*self.index.get(&u)?, *self.index.get(&v)?);
+        self.distance[i][j]?;
+
+        let mut path = vec![self.nodes[j]];
+        while j != i {
+            j = self.predecessor[i][j]?;
+            path.push(self.nodes[j]);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The eccentricity of a node is the distance to the
+    /// node furthest away from it. `None` means that some
+    /// node can't be reached at all i.e. the eccentricity
+    /// is infinite.
+    pub fn eccentricity(&self, node: NodeId) -> Option<Weight> {
+        let i = *self.index.get(&node)?;
+        self.distance[i].iter().try_fold(0, |max, d| Some(max.max((*d)?)))
+    }
+
+    /// The minimum eccentricity over all nodes, `None`
+    /// if every node has an infinite eccentricity.
+    pub fn radius(&self) -> Option<Weight> {
+        self.nodes.iter().filter_map(|n| self.eccentricity(*n)).min()
+    }
+
+    /// The maximum eccentricity over all nodes, `None`
+    /// if the graph is empty or some node can't reach
+    /// all of the others.
+    pub fn diameter(&self) -> Option<Weight> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        self.nodes
+            .iter()
+            .map(|n| self.eccentricity(*n))
+            .try_fold(0, |max, ecc| Some(max.max(ecc?)))
+    }
+
+    /// The nodes whose eccentricity equals the radius
+    pub fn center(&self) -> Vec<NodeId> {
+        let radius = match self.radius() {
+            Some(radius) => radius,
+            None => return Vec::new(),
+        };
+        self.nodes
+            .iter()
+            .copied()
+            .filter(|n| self.eccentricity(*n) == Some(radius))
+            .collect()
+    }
+}
+
+/// Floyd-Warshall algorithm, O(V^3) time and O(V^2) space.
+/// This is the one to use for dense graphs.
+///
+/// For every intermediate node `k` we check whether going
+/// `i -> k -> j` is shorter than the best `i -> j` found so far.
+/// Returns `None` if the graph contains a negative cycle.
+pub fn floyd_warshall(graph: &Graph) -> Option<DistanceMatrix> {
+    let mut matrix = DistanceMatrix::new(graph.nodes());
+    let n = matrix.nodes.len();
+
+    for (u, edges) in &graph.adjacency_list {
+        let i = matrix.index[u];
+        for (v, weight) in edges {
+            let j = matrix.index[v];
+            // keep the cheapest of any parallel edges
+            if matrix.distance[i][j].is_none_or(|d| *weight < d) {
+                matrix.distance[i][j] = Some(*weight);
+                matrix.predecessor[i][j] = Some(i);
+            }
+        }
+    }
+
+    for k in 0..n {
+        for i in 0..n {
+            let ik = match matrix.distance[i][k] {
+                Some(ik) => ik,
+                None => continue,
+            };
+            for j in 0..n {
+                let kj = match matrix.distance[k][j] {
+                    Some(kj) => kj,
+                    None => continue,
+                };
+                if matrix.distance[i][j].is_none_or(|ij| ik + kj < ij) {
+                    matrix.distance[i][j] = Some(ik + kj);
+                    matrix.predecessor[i][j] = matrix.predecessor[k][j];
+                }
+            }
+        }
+    }
+
+    // a node that can reach itself with a negative
+    // total weight sits on a negative cycle
+    if (0..n).any(|i| matrix.distance[i][i] < Some(0)) {
+        return None;
+    }
+
+    Some(matrix)
+}
+
+/// Johnson's algorithm, O(V * E * log V) time.
+/// This is the one to use for sparse weighted graphs.
+///
+/// 1. Bellman-Ford from a virtual source gives every node a
+///    potential `h(v)`.
+/// 2. Edges are reweighted as `w(u, v) + h(u) - h(v)` which
+///    makes them all non-negative without changing which
+///    paths are the shortest.
+/// 3. Dijkstra is run from every node on the reweighted graph
+///    and the distances are translated back.
+///
+/// Returns `None` if the graph contains a negative cycle.
+pub fn johnson(graph: &Graph) -> Option<DistanceMatrix> {
+    let mut matrix = DistanceMatrix::new(graph.nodes());
+    let potential = bellman_ford_potential(graph)?;
+
+    for source in 0..matrix.nodes.len() {
+        dijkstra(graph, &potential, source, &mut matrix);
+    }
+
+    Some(matrix)
+}
+
+/// Bellman-Ford from a virtual source connected to every
+/// node with a 0-weight edge, so every potential starts at 0.
+/// Returns `None` if a negative cycle is found.
+fn bellman_ford_potential(graph: &Graph) -> Option<HashMap<NodeId, Weight>> {
+    let mut potential: HashMap<NodeId, Weight> =
+        graph.adjacency_list.keys().map(|n| (*n, 0)).collect();
+
+    for _ in 0..=graph.adjacency_list.len() {
+        let mut changed = false;
+        for (u, edges) in &graph.adjacency_list {
+            let hu = potential[u];
+            for (v, weight) in edges {
+                if hu + weight < potential[v] {
+                    potential.insert(*v, hu + weight);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Some(potential);
+        }
+    }
+
+    // still relaxing after V + 1 rounds
+    None
+}
+
+fn dijkstra(
+    graph: &Graph,
+    potential: &HashMap<NodeId, Weight>,
+    source: usize,
+    matrix: &mut DistanceMatrix,
+) {
+    // reweighted distances, these are never negative
+    let mut distance: Vec<Option<Weight>> = vec![None; matrix.nodes.len()];
+    distance[source] = Some(0);
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0, source)));
+
+    while let Some(Reverse((current_distance, i))) = heap.pop() {
+        if distance[i].is_some_and(|d| current_distance > d) {
+            // stale heap entry, we already found a shorter way
+            continue;
+        }
+        let u = matrix.nodes[i];
+        for (v, weight) in &graph.adjacency_list[&u] {
+            let j = matrix.index[v];
+            let reweighted = weight + potential[&u] - potential[v];
+            let candidate = current_distance + reweighted;
+            if distance[j].is_none_or(|d| candidate < d) {
+                distance[j] = Some(candidate);
+                matrix.predecessor[source][j] = Some(i);
+                heap.push(Reverse((candidate, j)));
+            }
+        }
+    }
+
+    let hs = potential[&matrix.nodes[source]];
+    for (j, d) in distance.into_iter().enumerate() {
+        let hv = potential[&matrix.nodes[j]];
+        matrix.distance[source][j] = d.map(|d| d - hs + hv);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_weighted_directed_edge(0, 1, 3);
+        graph.add_weighted_directed_edge(0, 2, 8);
+        graph.add_weighted_directed_edge(0, 4, -4);
+        graph.add_weighted_directed_edge(1, 3, 1);
+        graph.add_weighted_directed_edge(1, 4, 7);
+        graph.add_weighted_directed_edge(2, 1, 4);
+        graph.add_weighted_directed_edge(3, 0, 2);
+        graph.add_weighted_directed_edge(3, 2, -5);
+        graph.add_weighted_directed_edge(4, 3, 6);
+        graph
+    }
+
+    #[test]
+    fn test_add_edge() {
+        let mut graph = Graph::new();
+        graph.add_weighted_directed_edge(0, 1, 5);
+        graph.add_directed_edge(1, 2);
+        assert_eq!(graph.nodes(), vec![0, 1, 2]);
+        assert_eq!(graph.adjacency_list.get(&0), Some(&vec![(1, 5)]));
+        assert_eq!(graph.adjacency_list.get(&1), Some(&vec![(2, 1)]));
+        assert_eq!(graph.adjacency_list.get(&2), Some(&vec![]));
+    }
+
+    #[test]
+    fn test_floyd_warshall_00() {
+        let graph = weighted_graph();
+        let matrix = floyd_warshall(&graph).unwrap();
+        assert_eq!(matrix.distance(0, 1), Some(1));
+        assert_eq!(matrix.distance(0, 2), Some(-3));
+        assert_eq!(matrix.distance(0, 3), Some(2));
+        assert_eq!(matrix.distance(2, 0), Some(7));
+        assert_eq!(matrix.distance(4, 2), Some(1));
+        assert_eq!(matrix.path(0, 1), Some(vec![0, 4, 3, 2, 1]));
+        assert_eq!(matrix.path(3, 3), Some(vec![3]));
+    }
+
+    #[test]
+    fn test_johnson_matches_floyd_warshall() {
+        let graph = weighted_graph();
+        let fw = floyd_warshall(&graph).unwrap();
+        let johnson = johnson(&graph).unwrap();
+        for u in graph.nodes() {
+            for v in graph.nodes() {
+                assert_eq!(fw.distance(u, v), johnson.distance(u, v));
+                assert_eq!(fw.path(u, v), johnson.path(u, v));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_directed_edge(0, 1);
+        graph.add_node(2);
+        // 0 ---> 1
+        //
+        // 2
+        for matrix in [floyd_warshall(&graph), johnson(&graph)] {
+            let matrix = matrix.unwrap();
+            assert_eq!(matrix.distance(0, 1), Some(1));
+            assert_eq!(matrix.distance(1, 0), None);
+            assert_eq!(matrix.path(0, 2), None);
+            assert_eq!(matrix.distance(0, 42), None);
+        }
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let mut graph = Graph::new();
+        graph.add_weighted_directed_edge(0, 1, 1);
+        graph.add_weighted_directed_edge(1, 2, -3);
+        graph.add_weighted_directed_edge(2, 0, 1);
+        assert!(floyd_warshall(&graph).is_none());
+        assert!(johnson(&graph).is_none());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(1, 4);
+        // 0 <--> 1 <--> 2 <--> 3
+        //        ^
+        //        |
+        //        v
+        //        4
+        let matrix = floyd_warshall(&graph).unwrap();
+        assert_eq!(matrix.eccentricity(0), Some(3));
+        assert_eq!(matrix.eccentricity(1), Some(2));
+        assert_eq!(matrix.eccentricity(2), Some(2));
+        assert_eq!(matrix.radius(), Some(2));
+        assert_eq!(matrix.diameter(), Some(3));
+        assert_eq!(matrix.center(), vec![1, 2]);
+
+        graph.add_node(5);
+        let matrix = johnson(&graph).unwrap();
+        // nobody can reach the isolated node 5 and
+        // it can't reach anyone either
+        assert_eq!(matrix.eccentricity(0), None);
+        assert_eq!(matrix.eccentricity(5), None);
+        assert_eq!(matrix.radius(), None);
+        assert_eq!(matrix.diameter(), None);
+        assert_eq!(matrix.center(), vec![]);
+    }
+}
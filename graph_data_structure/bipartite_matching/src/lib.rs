@@ -0,0 +1,422 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use graph_colouring::{two_colouring, OddCycle};
+
+pub use graph_colouring::{Graph, NodeId};
+
+pub type Cost = i64;
+
+/// Hopcroft-Karp algorithm.
+///
+/// A maximum matching between the nodes in `left` and their
+/// neighbours, as `(left node, right node)` pairs sorted by
+/// the left node. Edges between two nodes of `left` are
+/// ignored.
+///
+/// Every round a BFS from all the unmatched left nodes finds
+/// the length of the shortest augmenting paths (alternating
+/// unmatched and matched edges, ending in an unmatched right
+/// node), then DFS flips as many node-disjoint paths of that
+/// length as it can find. Only O(sqrt(V)) rounds are needed,
+/// making it O(E * sqrt(V)) overall.
+pub fn hopcroft_karp(graph: &Graph, left: &[NodeId]) -> Vec<(NodeId, NodeId)> {
+    let mut matching = Matching {
+        graph,
+        left: left.iter().copied().collect(),
+        pair_of_left: HashMap::new(),
+        pair_of_right: HashMap::new(),
+        layer: HashMap::new(),
+        free_layer: None,
+    };
+
+    while matching.find_layers() {
+        for node in left {
+            if !matching.pair_of_left.contains_key(node) {
+                matching.augment(*node);
+            }
+        }
+    }
+
+    let mut pairs: Vec<(NodeId, NodeId)> =
+        matching.pair_of_left.into_iter().collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+struct Matching<'a> {
+    graph: &'a Graph,
+    left: HashSet<NodeId>,
+    pair_of_left: HashMap<NodeId, NodeId>,
+    pair_of_right: HashMap<NodeId, NodeId>,
+    // BFS layer of the left nodes, dead ends are removed
+    layer: HashMap<NodeId, usize>,
+    // the layer whose nodes have an edge to an unmatched right
+    // node, where the shortest augmenting paths end
+    free_layer: Option<usize>,
+}
+
+impl Matching<'_> {
+    fn right_neighbours(&self, node: NodeId) -> Vec<NodeId> {
+        self.graph
+            .neighbours(node)
+            .iter()
+            .copied()
+            .filter(|neighbour| !self.left.contains(neighbour))
+            .collect()
+    }
+
+    /// Layers the left nodes by how many matched edges it
+    /// takes to reach them from an unmatched one, stopping at
+    /// the first layer with an edge to an unmatched right
+    /// node. Returns whether some augmenting path exists.
+    fn find_layers(&mut self) -> bool {
+        self.layer.clear();
+        self.free_layer = None;
+        let mut queue = VecDeque::new();
+        for node in &self.left {
+            if !self.pair_of_left.contains_key(node) {
+                self.layer.insert(*node, 0);
+                queue.push_back(*node);
+            }
+        }
+
+        while let Some(current_node) = queue.pop_front() {
+            let layer = self.layer[&current_node];
+            if self.free_layer.is_some_and(|free_layer| layer > free_layer) {
+                break;
+            }
+            for neighbour in self.right_neighbours(current_node) {
+                match self.pair_of_right.get(&neighbour) {
+                    None => {
+                        self.free_layer.get_or_insert(layer);
+                    }
+                    // no need to go deeper than the shortest
+                    // augmenting paths
+                    Some(next)
+                        if self.free_layer.is_none()
+                            && !self.layer.contains_key(next) =>
+                    {
+                        self.layer.insert(*next, layer + 1);
+                        queue.push_back(*next);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        self.free_layer.is_some()
+    }
+
+    /// Looks for an augmenting path from `current_node` that
+    /// goes one layer down at every step, ending at an
+    /// unmatched right node of the last layer, and flips it
+    fn augment(&mut self, current_node: NodeId) -> bool {
+        let Some(layer) = self.layer.get(&current_node).copied() else {
+            return false;
+        };
+        let last_layer = self.free_layer == Some(layer);
+
+        for neighbour in self.right_neighbours(current_node) {
+            let flipped = match self.pair_of_right.get(&neighbour).copied() {
+                None => last_layer,
+                Some(next) => {
+                    !last_layer
+                        && self.layer.get(&next) == Some(&(layer + 1))
+                        && self.augment(next)
+                }
+            };
+            if flipped {
+                self.pair_of_left.insert(current_node, neighbour);
+                self.pair_of_right.insert(neighbour, current_node);
+                return true;
+            }
+        }
+
+        // nothing to be found from here this round
+        self.layer.remove(&current_node);
+        false
+    }
+}
+
+/// A maximum matching of a bipartite graph, the sides are
+/// found with `two_colouring`. Pairs are `(u, v)` with `u` on
+/// the side holding the smallest node of each component,
+/// sorted. Returns the odd cycle if the graph isn't
+/// bipartite.
+pub fn maximum_matching(
+    graph: &Graph,
+) -> Result<Vec<(NodeId, NodeId)>, OddCycle> {
+    let (left, _) = two_colouring(graph)?;
+    Ok(hopcroft_karp(graph, &left))
+}
+
+/// The cheapest way to give every row a column of its own
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    /// `(row, column)` pairs sorted by row. If there are more
+    /// rows than columns some rows stay unassigned, and the
+    /// other way round.
+    pub pairs: Vec<(usize, usize)>,
+    pub total_cost: Cost,
+}
+
+/// Hungarian algorithm for the assignment problem.
+///
+/// `costs[i][j]` is the cost of giving column `j` to row `i`.
+/// Rows are added one at a time, each time finding the
+/// cheapest way to make room for it with a Dijkstra-like
+/// search over the columns. Potentials on rows and columns
+/// keep the reduced costs non-negative, which is what lets
+/// that search work with negative costs too. O(n^2 * m) for
+/// `n` rows and `m >= n` columns.
+///
+/// Returns `None` if the rows don't all have the same
+/// length.
+pub fn hungarian(costs: &[Vec<Cost>]) -> Option<Assignment> {
+    let columns = costs.first().map_or(0, |row| row.len());
+    if costs.iter().any(|row| row.len() != columns) {
+        return None;
+    }
+
+    if costs.len() > columns {
+        // give every column a row instead
+        let transposed: Vec<Vec<Cost>> = (0..columns)
+            .map(|j| costs.iter().map(|row| row[j]).collect())
+            .collect();
+        let mut assignment = hungarian(&transposed)?;
+        for pair in &mut assignment.pairs {
+            *pair = (pair.1, pair.0);
+        }
+        assignment.pairs.sort_unstable();
+        return Some(assignment);
+    }
+
+    let (n, m) = (costs.len(), columns);
+    // everything is 1-based, row 0 and column 0 are a dummy
+    // the row being added starts from
+    let mut row_potential = vec![0; n + 1];
+    let mut column_potential = vec![0; m + 1];
+    let mut row_of_column = vec![0; m + 1];
+    let mut previous_column = vec![0; m + 1];
+
+    for row in 1..=n {
+        row_of_column[0] = row;
+        let mut column = 0;
+        let mut min_reduced = vec![Cost::MAX; m + 1];
+        let mut used = vec![false; m + 1];
+
+        // grow a tree of tight edges until it reaches a free
+        // column
+        loop {
+            used[column] = true;
+            let current_row = row_of_column[column];
+            let mut delta = Cost::MAX;
+            let mut next_column = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let reduced = costs[current_row - 1][j - 1]
+                    - row_potential[current_row]
+                    - column_potential[j];
+                if reduced < min_reduced[j] {
+                    min_reduced[j] = reduced;
+                    previous_column[j] = column;
+                }
+                if min_reduced[j] < delta {
+                    delta = min_reduced[j];
+                    next_column = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    row_potential[row_of_column[j]] += delta;
+                    column_potential[j] -= delta;
+                } else {
+                    min_reduced[j] -= delta;
+                }
+            }
+            column = next_column;
+            if row_of_column[column] == 0 {
+                break;
+            }
+        }
+
+        // shift the rows along the path back to the dummy
+        while column != 0 {
+            let previous = previous_column[column];
+            row_of_column[column] = row_of_column[previous];
+            column = previous;
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (1..=m)
+        .filter(|j| row_of_column[*j] != 0)
+        .map(|j| (row_of_column[j] - 1, j - 1))
+        .collect();
+    pairs.sort_unstable();
+    let total_cost = pairs.iter().map(|(i, j)| costs[*i][*j]).sum();
+    Some(Assignment { pairs, total_cost })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::Rng;
+
+    fn assert_matching(graph: &Graph, pairs: &[(NodeId, NodeId)]) {
+        let mut used = HashSet::new();
+        for (u, v) in pairs {
+            assert!(graph.neighbours(*u).contains(v));
+            assert!(used.insert(*u));
+            assert!(used.insert(*v));
+        }
+    }
+
+    /// Size of the largest matching of nodes not in `used`
+    fn brute_force_matching(
+        edges: &[(NodeId, NodeId)],
+        used: &mut HashSet<NodeId>,
+    ) -> usize {
+        let Some(((u, v), rest)) = edges.split_first() else {
+            return 0;
+        };
+        let mut best = brute_force_matching(rest, used);
+        if !used.contains(u) && !used.contains(v) {
+            used.insert(*u);
+            used.insert(*v);
+            best = best.max(1 + brute_force_matching(rest, used));
+            used.remove(u);
+            used.remove(v);
+        }
+        best
+    }
+
+    /// Cheapest assignment of rows `row..` to unused columns
+    fn brute_force_assignment(
+        costs: &[Vec<Cost>],
+        row: usize,
+        used: &mut Vec<bool>,
+    ) -> Cost {
+        if row == costs.len() {
+            return 0;
+        }
+        let mut best = Cost::MAX;
+        for column in 0..used.len() {
+            if !used[column] {
+                used[column] = true;
+                let cost = costs[row][column]
+                    + brute_force_assignment(costs, row + 1, used);
+                best = best.min(cost);
+                used[column] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_hopcroft_karp_00() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 10);
+        graph.add_undirected_edge(0, 11);
+        graph.add_undirected_edge(1, 10);
+        graph.add_undirected_edge(2, 11);
+        graph.add_undirected_edge(2, 12);
+        graph.add_undirected_edge(3, 12);
+        // 0    1    2    3
+        // | \  |    | \  |
+        // |  \ |    |  \ |
+        // 10   11 --    12
+        let pairs = hopcroft_karp(&graph, &[0, 1, 2, 3]);
+        assert_matching(&graph, &pairs);
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(maximum_matching(&graph).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_hopcroft_karp_01() {
+        // a greedy pick of 0 <--> 10 first needs undoing
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 10);
+        graph.add_undirected_edge(0, 11);
+        graph.add_undirected_edge(1, 10);
+        graph.add_node(2);
+        let pairs = hopcroft_karp(&graph, &[0, 1, 2]);
+        assert_eq!(pairs, vec![(0, 11), (1, 10)]);
+
+        graph.add_undirected_edge(0, 1);
+        assert!(maximum_matching(&graph).is_err());
+        // the edge between two left nodes doesn't count
+        assert_eq!(hopcroft_karp(&graph, &[0, 1, 2]), pairs);
+    }
+
+    #[test]
+    fn test_matching_brute_force() {
+        let mut rng = Rng::new(7);
+        for _ in 0..30 {
+            let mut graph = Graph::new();
+            let mut edges = Vec::new();
+            let (left, right) = (1 + rng.below(6), 1 + rng.below(6));
+            for _ in 0..rng.below(12) {
+                let u = rng.below(left);
+                let v = 100 + rng.below(right);
+                graph.add_undirected_edge(u, v);
+                edges.push((u, v));
+            }
+            let left: Vec<NodeId> = (0..left).collect();
+            let pairs = hopcroft_karp(&graph, &left);
+            assert_matching(&graph, &pairs);
+            let best = brute_force_matching(&edges, &mut HashSet::new());
+            assert_eq!(pairs.len(), best);
+        }
+    }
+
+    #[test]
+    fn test_hungarian_00() {
+        let costs = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        let assignment = hungarian(&costs).unwrap();
+        assert_eq!(assignment.pairs, vec![(0, 1), (1, 0), (2, 2)]);
+        assert_eq!(assignment.total_cost, 5);
+    }
+
+    #[test]
+    fn test_hungarian_01() {
+        // more columns than rows, and the other way round
+        let costs = vec![vec![7, 3, 9, 1], vec![2, 8, 6, 4]];
+        let assignment = hungarian(&costs).unwrap();
+        assert_eq!(assignment.pairs, vec![(0, 3), (1, 0)]);
+        assert_eq!(assignment.total_cost, 3);
+
+        let transposed = vec![vec![7, 2], vec![3, 8], vec![9, 6], vec![1, 4]];
+        let assignment = hungarian(&transposed).unwrap();
+        assert_eq!(assignment.pairs, vec![(0, 1), (3, 0)]);
+        assert_eq!(assignment.total_cost, 3);
+
+        let costs = vec![vec![-5, 2], vec![3, -1]];
+        assert_eq!(hungarian(&costs).unwrap().total_cost, -6);
+
+        assert_eq!(
+            hungarian(&[]),
+            Some(Assignment { pairs: vec![], total_cost: 0 })
+        );
+        assert_eq!(hungarian(&[vec![1, 2], vec![3]]), None);
+    }
+
+    #[test]
+    fn test_hungarian_brute_force() {
+        let mut rng = Rng::new(42);
+        for _ in 0..30 {
+            let rows = 1 + rng.below(5);
+            let columns = rows + rng.below(3);
+            let costs: Vec<Vec<Cost>> = (0..rows)
+                .map(|_| {
+                    (0..columns).map(|_| rng.below(50) as Cost - 10).collect()
+                })
+                .collect();
+            let assignment = hungarian(&costs).unwrap();
+            assert_eq!(assignment.pairs.len(), rows);
+            let best =
+                brute_force_assignment(&costs, 0, &mut vec![false; columns]);
+            assert_eq!(assignment.total_cost, best);
+        }
+    }
+}
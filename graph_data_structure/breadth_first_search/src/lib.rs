@@ -1,7 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    usize,
-};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub type NodeId = usize;
 
@@ -11,46 +8,106 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self { adjacency_list: HashMap::new() }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.adjacency_list.get(&node).map_or(&[], |n| n.as_slice())
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Breadth-first search algorithm
 /// We cannot implement this recursively
 /// so iterative approach it is
-fn breadth_first_search(graph: Graph, start_node: NodeId) -> Vec<NodeId> {
+pub fn breadth_first_search(graph: Graph, start_node: NodeId) -> Vec<NodeId> {
+    breadth_first_search_by(start_node, |node| {
+        graph.neighbours(node).iter().copied()
+    })
+    .order
+}
+
+/// Everything a breadth-first search learns on the way
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BfsTree {
+    /// The nodes in the order they were visited
+    pub order: Vec<NodeId>,
+    /// The node each node was first reached from, the start
+    /// node has none
+    pub parent: HashMap<NodeId, NodeId>,
+    /// Number of edges on the shortest path from the start
+    pub distance: HashMap<NodeId, usize>,
+}
+
+impl BfsTree {
+    /// The shortest path from the start node to `node`,
+    /// `None` if the search never got there
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        if !self.distance.contains_key(&node) {
+            return None;
+        }
+        let mut path = vec![node];
+        let mut current_node = node;
+        while let Some(parent) = self.parent.get(&current_node) {
+            current_node = *parent;
+            path.push(current_node);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search where the caller decides what the
+/// neighbours of a node are. Handy when the edges aren't
+/// stored in a `Graph`, e.g. only following the edges of a
+/// flow network that still have capacity left.
+pub fn breadth_first_search_by<F, I>(
+    start_node: NodeId,
+    mut neighbours: F,
+) -> BfsTree
+where
+    F: FnMut(NodeId) -> I,
+    I: IntoIterator<Item = NodeId>,
+{
+    let mut tree = BfsTree::default();
     let mut visited = HashSet::new();
-    let mut traversel = Vec::new();
     let mut queue = VecDeque::new();
     visited.insert(start_node);
+    tree.distance.insert(start_node, 0);
     queue.push_back(start_node);
-    while !queue.is_empty() {
-        let current_node = queue.pop_front().unwrap();
-        traversel.push(current_node);
-        for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
-            if !visited.contains(neighbour) {
-                visited.insert(*neighbour);
-                queue.push_back(*neighbour);
+    while let Some(current_node) = queue.pop_front() {
+        tree.order.push(current_node);
+        for neighbour in neighbours(current_node) {
+            if visited.insert(neighbour) {
+                tree.parent.insert(neighbour, current_node);
+                tree.distance
+                    .insert(neighbour, tree.distance[&current_node] + 1);
+                queue.push_back(neighbour);
             }
         }
     }
-    traversel
+    tree
 }
 
 #[cfg(test)]
@@ -136,4 +193,37 @@ mod tests {
         let traversal = breadth_first_search(graph, 3);
         assert_eq!(traversal, vec![3, 5]);
     }
+
+    #[test]
+    fn test_bfs_tree() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 4);
+        graph.add_directed_edge(5, 0);
+        // 0 ---> 1
+        // |      |
+        // v      v
+        // 2 ---> 3 ---> 4
+        let tree = breadth_first_search_by(0, |node| {
+            graph.neighbours(node).iter().copied()
+        });
+        assert_eq!(tree.order, vec![0, 1, 2, 3, 4]);
+        assert_eq!(tree.distance[&4], 3);
+        assert_eq!(tree.path_to(4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(tree.path_to(0), Some(vec![0]));
+        assert_eq!(tree.path_to(5), None);
+
+        // skip the edge 1 --> 3
+        let tree = breadth_first_search_by(0, |node| {
+            graph
+                .neighbours(node)
+                .iter()
+                .copied()
+                .filter(move |n| (node, *n) != (1, 3))
+        });
+        assert_eq!(tree.path_to(4), Some(vec![0, 2, 3, 4]));
+    }
 }
@@ -0,0 +1,260 @@
+use std::collections::{BTreeSet, HashMap};
+
+pub type NodeId = usize;
+
+/// An undirected graph
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // add a bi-directional edge u <--> v
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+}
+
+/// Everything Tarjan's low-link depth-first search finds
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LowLink {
+    /// Edges whose removal disconnects the graph, as `(u, v)`
+    /// with `u < v`, sorted
+    pub bridges: Vec<(NodeId, NodeId)>,
+    /// Nodes whose removal disconnects the graph, sorted
+    pub articulation_points: Vec<NodeId>,
+    /// The nodes of each maximal piece of the graph that stays
+    /// connected after removing any single node. Neighbouring
+    /// pieces share an articulation point, nodes without any
+    /// edges aren't part of any piece.
+    pub biconnected_components: Vec<Vec<NodeId>>,
+}
+
+/// Tarjan's low-link algorithm.
+///
+/// A DFS numbers the nodes in the order it discovers them.
+/// The `low` value of a node is the smallest number reachable
+/// from its DFS subtree using at most one edge that isn't a
+/// tree edge. For a tree edge `v --> w`:
+///
+/// - if `low[w] > disc[v]` nothing below `w` reaches `v` or
+///   above without this edge: it's a bridge
+/// - if `low[w] >= disc[v]` nothing below `w` gets past `v`:
+///   `v` is an articulation point (the DFS root only if it
+///   has more than one child) and the edges pushed since
+///   `v --> w` form a biconnected component
+pub fn low_link(graph: &Graph) -> LowLink {
+    let mut search = Search {
+        graph,
+        disc: HashMap::new(),
+        low: HashMap::new(),
+        edge_stack: Vec::new(),
+        articulation_points: BTreeSet::new(),
+        result: LowLink::default(),
+    };
+
+    for node in graph.nodes() {
+        if !search.disc.contains_key(&node) {
+            search.visit(node, None);
+        }
+    }
+
+    let mut result = search.result;
+    result.articulation_points =
+        search.articulation_points.into_iter().collect();
+    result.bridges.sort_unstable();
+    result.biconnected_components.sort_unstable();
+    result
+}
+
+pub fn bridges(graph: &Graph) -> Vec<(NodeId, NodeId)> {
+    low_link(graph).bridges
+}
+
+pub fn articulation_points(graph: &Graph) -> Vec<NodeId> {
+    low_link(graph).articulation_points
+}
+
+pub fn biconnected_components(graph: &Graph) -> Vec<Vec<NodeId>> {
+    low_link(graph).biconnected_components
+}
+
+struct Search<'a> {
+    graph: &'a Graph,
+    disc: HashMap<NodeId, usize>,
+    low: HashMap<NodeId, usize>,
+    edge_stack: Vec<(NodeId, NodeId)>,
+    articulation_points: BTreeSet<NodeId>,
+    result: LowLink,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, current_node: NodeId, parent: Option<NodeId>) {
+        let disc = self.disc.len();
+        self.disc.insert(current_node, disc);
+        self.low.insert(current_node, disc);
+
+        let mut children = 0;
+        // we came in through one edge from the parent, a
+        // second (parallel) edge to it is a real way back
+        let mut skipped_parent = false;
+        for neighbour in &self.graph.adjacency_list[&current_node] {
+            let neighbour = *neighbour;
+            if Some(neighbour) == parent && !skipped_parent {
+                skipped_parent = true;
+                continue;
+            }
+
+            match self.disc.get(&neighbour) {
+                None => {
+                    children += 1;
+                    self.edge_stack.push((current_node, neighbour));
+                    self.visit(neighbour, Some(current_node));
+
+                    let low =
+                        self.low[&current_node].min(self.low[&neighbour]);
+                    self.low.insert(current_node, low);
+
+                    if self.low[&neighbour] > disc {
+                        let bridge = (
+                            current_node.min(neighbour),
+                            current_node.max(neighbour),
+                        );
+                        self.result.bridges.push(bridge);
+                    }
+                    if self.low[&neighbour] >= disc {
+                        if parent.is_some() || children > 1 {
+                            self.articulation_points.insert(current_node);
+                        }
+                        self.pop_component((current_node, neighbour));
+                    }
+                }
+                Some(neighbour_disc) if *neighbour_disc < disc => {
+                    // back edge to an ancestor
+                    self.edge_stack.push((current_node, neighbour));
+                    let low = self.low[&current_node].min(*neighbour_disc);
+                    self.low.insert(current_node, low);
+                }
+                // the other end of a back edge we've already
+                // pushed from the descendant's side
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn pop_component(&mut self, until: (NodeId, NodeId)) {
+        let mut nodes = BTreeSet::new();
+        while let Some(edge) = self.edge_stack.pop() {
+            nodes.insert(edge.0);
+            nodes.insert(edge.1);
+            if edge == until {
+                break;
+            }
+        }
+        self.result.biconnected_components.push(nodes.into_iter().collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_link_00() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 3);
+        graph.add_undirected_edge(5, 6);
+        // 0 <--> 1 <--> 3 <--> 4
+        // ^      ^      ^      ^
+        // |      |      |      |
+        // v      |      v      |
+        // 2 <----       5 <----
+        //               ^
+        //               |
+        //               v
+        //               6
+        let result = low_link(&graph);
+        assert_eq!(result.bridges, vec![(1, 3), (5, 6)]);
+        assert_eq!(result.articulation_points, vec![1, 3, 5]);
+        assert_eq!(
+            result.biconnected_components,
+            vec![vec![0, 1, 2], vec![1, 3], vec![3, 4, 5], vec![5, 6]]
+        );
+    }
+
+    #[test]
+    fn test_low_link_01() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 0);
+        graph.add_node(4);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      v
+        // 3 <--> 2
+        //
+        // 4
+        assert_eq!(bridges(&graph), vec![]);
+        assert_eq!(articulation_points(&graph), vec![]);
+        assert_eq!(biconnected_components(&graph), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_path() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        // 0 <--> 1 <--> 2 <--> 3
+        assert_eq!(bridges(&graph), vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(articulation_points(&graph), vec![1, 2]);
+        assert_eq!(
+            biconnected_components(&graph),
+            vec![vec![0, 1], vec![1, 2], vec![2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_root_articulation_point() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(3, 1);
+        // 1 <--> 0 <--> 2
+        // ^
+        // |   (two edges between 1 and 3)
+        // v
+        // 3
+        let result = low_link(&graph);
+        assert_eq!(result.articulation_points, vec![0, 1]);
+        // parallel edges are never a bridge
+        assert_eq!(result.bridges, vec![(0, 1), (0, 2)]);
+    }
+}
@@ -0,0 +1,423 @@
+//! How central or important every node of a graph is.
+//!
+//! Each function returns `(node, score)` for every node of
+//! the graph, highest score first and ties in ascending node
+//! order. Edges are followed the way they point, so for an
+//! undirected graph every edge has to be in both neighbour
+//! lists, the way `add_undirected_edge` puts it there.
+use std::collections::VecDeque;
+
+pub use compressed_graph::{Adjacency, CsrGraph, Graph, NodeId};
+
+pub type Score = f64;
+
+/// PageRank stops after this many rounds even if it hasn't
+/// converged yet
+pub const MAX_ITERATIONS: usize = 1000;
+
+fn ranked<T: PartialOrd>(mut scores: Vec<(NodeId, T)>) -> Vec<(NodeId, T)> {
+    scores.sort_by(|(u, a), (v, b)| {
+        b.partial_cmp(a).unwrap().then_with(|| u.cmp(v))
+    });
+    scores
+}
+
+/// PageRank by power iteration.
+///
+/// A random surfer follows one of the edges of the node it is
+/// on with probability `damping` and jumps to a random node
+/// otherwise. A node's rank is the share of time the surfer
+/// spends there, so the ranks add up to 1. A node without
+/// edges out, like a paper that cites nothing, sends the
+/// surfer to a random node. Stops once the ranks change by
+/// less than `tolerance` in total over a round.
+pub fn pagerank<G: Adjacency>(
+    graph: &G,
+    damping: f64,
+    tolerance: f64,
+) -> Vec<(NodeId, Score)> {
+    let nodes = graph.nodes();
+    let n = nodes.len() as f64;
+    let mut rank = vec![0.0; graph.node_bound()];
+    for node in &nodes {
+        rank[*node] = 1.0 / n;
+    }
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = nodes
+            .iter()
+            .filter(|node| graph.neighbours(**node).is_empty())
+            .map(|node| rank[*node])
+            .sum();
+        let base = (1.0 - damping) / n + damping * dangling / n;
+        let mut next = vec![0.0; graph.node_bound()];
+        for node in &nodes {
+            next[*node] += base;
+            let neighbours = graph.neighbours(*node);
+            let share = damping * rank[*node] / neighbours.len() as f64;
+            for neighbour in neighbours {
+                next[*neighbour] += share;
+            }
+        }
+        let change: f64 =
+            nodes.iter().map(|node| (next[*node] - rank[*node]).abs()).sum();
+        rank = next;
+        if change < tolerance {
+            break;
+        }
+    }
+    ranked(nodes.into_iter().map(|node| (node, rank[node])).collect())
+}
+
+/// Number of edges out of each node, as a fraction of the
+/// other nodes it could have edges to
+pub fn degree_centrality<G: Adjacency>(graph: &G) -> Vec<(NodeId, Score)> {
+    let nodes = graph.nodes();
+    let scale = 1.0 / (nodes.len().max(2) - 1) as f64;
+    ranked(
+        nodes
+            .into_iter()
+            .map(|node| (node, graph.neighbours(node).len() as f64 * scale))
+            .collect(),
+    )
+}
+
+/// Number of edges into each node, as a fraction of the other
+/// nodes it could have edges from. For a citation network,
+/// how often each paper is cited.
+pub fn in_degree_centrality<G: Adjacency>(graph: &G) -> Vec<(NodeId, Score)> {
+    let nodes = graph.nodes();
+    let scale = 1.0 / (nodes.len().max(2) - 1) as f64;
+    let mut in_degree = vec![0; graph.node_bound()];
+    for node in &nodes {
+        for neighbour in graph.neighbours(*node) {
+            in_degree[*neighbour] += 1;
+        }
+    }
+    ranked(
+        nodes
+            .into_iter()
+            .map(|node| (node, in_degree[node] as f64 * scale))
+            .collect(),
+    )
+}
+
+/// The number of edges from `start` to every node, `None`
+/// for the ones it can't reach
+fn distances<G: Adjacency>(graph: &G, start: NodeId) -> Vec<Option<usize>> {
+    let mut distance = vec![None; graph.node_bound()];
+    let mut queue = VecDeque::new();
+    distance[start] = Some(0);
+    queue.push_back(start);
+    while let Some(current_node) = queue.pop_front() {
+        let next = distance[current_node].map(|d| d + 1);
+        for neighbour in graph.neighbours(current_node) {
+            if distance[*neighbour].is_none() {
+                distance[*neighbour] = next;
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+    distance
+}
+
+/// How close each node is to the nodes it can reach: the
+/// number of them over the sum of the distances to them.
+/// Scaled by the share of the other nodes it reaches at all,
+/// so that a node which is close to the two nodes of a tiny
+/// component doesn't beat one that is a bit further from
+/// everything else. A node reaching nothing scores 0.
+pub fn closeness_centrality<G: Adjacency>(graph: &G) -> Vec<(NodeId, Score)> {
+    let nodes = graph.nodes();
+    let others = nodes.len().saturating_sub(1) as f64;
+    let scores = nodes
+        .iter()
+        .map(|node| {
+            let distances = distances(graph, *node);
+            let reached: Vec<usize> =
+                distances.into_iter().flatten().filter(|d| *d > 0).collect();
+            let total: usize = reached.iter().sum();
+            let score = if total == 0 {
+                0.0
+            } else {
+                let reached = reached.len() as f64;
+                reached / total as f64 * reached / others
+            };
+            (*node, score)
+        })
+        .collect();
+    ranked(scores)
+}
+
+/// Brandes' algorithm for betweenness centrality: for every
+/// pair of nodes, the share of the shortest paths between
+/// them that go through each node in the middle, added up.
+///
+/// A BFS from each node `s` counts the shortest paths
+/// `sigma` to every node and remembers the nodes they come
+/// through. Going back through the nodes from the furthest,
+/// each one hands its dependency on to those predecessors in
+/// proportion to how many of its paths come through them.
+/// O(n * e) time instead of looking at every pair.
+///
+/// Normalised by the number of ordered pairs of other nodes,
+/// `(n - 1) * (n - 2)`, which gives the same scores whether
+/// an undirected graph's pairs are counted once or twice.
+pub fn betweenness_centrality<G: Adjacency>(
+    graph: &G,
+) -> Vec<(NodeId, Score)> {
+    let nodes = graph.nodes();
+    let bound = graph.node_bound();
+    let mut betweenness = vec![0.0; bound];
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut queue = VecDeque::new();
+
+    for s in &nodes {
+        let mut predecessors: Vec<Vec<NodeId>> = vec![Vec::new(); bound];
+        let mut sigma = vec![0.0; bound];
+        let mut distance = vec![None; bound];
+        sigma[*s] = 1.0;
+        distance[*s] = Some(0);
+        order.clear();
+        queue.push_back(*s);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            let next = distance[v].map(|d: usize| d + 1);
+            for w in graph.neighbours(v) {
+                if distance[*w].is_none() {
+                    distance[*w] = next;
+                    queue.push_back(*w);
+                }
+                if distance[*w] == next {
+                    sigma[*w] += sigma[v];
+                    predecessors[*w].push(v);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0; bound];
+        for w in order.iter().rev() {
+            for v in &predecessors[*w] {
+                dependency[*v] +=
+                    sigma[*v] / sigma[*w] * (1.0 + dependency[*w]);
+            }
+            if w != s {
+                betweenness[*w] += dependency[*w];
+            }
+        }
+    }
+
+    let pairs = (nodes.len().saturating_sub(1) * nodes.len().saturating_sub(2))
+        .max(1) as f64;
+    ranked(
+        nodes
+            .into_iter()
+            .map(|node| (node, betweenness[node] / pairs))
+            .collect(),
+    )
+}
+
+/// The core number of every node of an undirected graph: the
+/// largest `k` such that the node belongs to the k-core, the
+/// biggest part of the graph where every node has at least
+/// `k` neighbours within that part.
+///
+/// Batagelj and Zaversnik's algorithm: keeps taking out a
+/// node of the lowest remaining degree, whose degree at that
+/// point is its core number. Nodes wait in buckets by degree
+/// so this takes O(n + e) time. A self loop counts twice
+/// towards the degree, a parallel edge once more.
+pub fn core_numbers<G: Adjacency>(graph: &G) -> Vec<(NodeId, usize)> {
+    let nodes = graph.nodes();
+    let mut degree = vec![0; graph.node_bound()];
+    for node in &nodes {
+        degree[*node] = graph.neighbours(*node).len();
+    }
+    let max_degree = nodes.iter().map(|node| degree[*node]).max().unwrap_or(0);
+    let mut buckets: Vec<Vec<NodeId>> = vec![Vec::new(); max_degree + 1];
+    for node in &nodes {
+        buckets[degree[*node]].push(*node);
+    }
+
+    let mut core = vec![None; graph.node_bound()];
+    let mut k = 0;
+    while k <= max_degree {
+        // a node sits in the bucket of every degree it had on
+        // the way down, only the lowest one counts
+        let Some(node) = buckets[k].pop() else {
+            k += 1;
+            continue;
+        };
+        if core[node].is_some() || degree[node] != k {
+            continue;
+        }
+        core[node] = Some(k);
+        for neighbour in graph.neighbours(node) {
+            if core[*neighbour].is_none() && degree[*neighbour] > k {
+                degree[*neighbour] -= 1;
+                buckets[degree[*neighbour]].push(*neighbour);
+            }
+        }
+    }
+    ranked(nodes.into_iter().map(|node| (node, core[node].unwrap())).collect())
+}
+
+/// The nodes of the k-core in ascending order
+pub fn k_core<G: Adjacency>(graph: &G, k: usize) -> Vec<NodeId> {
+    let mut nodes: Vec<NodeId> = core_numbers(graph)
+        .into_iter()
+        .filter(|(_, core)| *core >= k)
+        .map(|(node, _)| node)
+        .collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::{complete, cycle, grid, path, random_tree, star};
+
+    fn close(scores: &[(NodeId, Score)], expected: &[(NodeId, Score)]) {
+        assert_eq!(scores.len(), expected.len(), "{:?}", scores);
+        for ((u, a), (v, b)) in scores.iter().zip(expected) {
+            assert_eq!(u, v, "{:?}", scores);
+            assert!((a - b).abs() < 1e-6, "{:?}", scores);
+        }
+    }
+
+    #[test]
+    fn test_pagerank() {
+        // everyone cites 0, 0 cites 1
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 0);
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(3, 0);
+        graph.add_directed_edge(3, 2);
+        graph.add_node(4);
+        // 4   3 ---> 2
+        //     |      |
+        //     v      |
+        //     0 <----
+        //     ^
+        //     |
+        //     v
+        //     1
+        let ranks = pagerank(&graph, 0.85, 1e-10);
+        let order: Vec<NodeId> = ranks.iter().map(|(node, _)| *node).collect();
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+        let total: Score = ranks.iter().map(|(_, rank)| rank).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        // 3 and 4 only ever get the random jumps
+        assert!((ranks[3].1 - ranks[4].1).abs() < 1e-9);
+
+        let ranks = pagerank(&cycle(5, true).build::<Graph>(), 0.85, 1e-10);
+        close(&ranks, &[(0, 0.2), (1, 0.2), (2, 0.2), (3, 0.2), (4, 0.2)]);
+        assert_eq!(pagerank(&Graph::new(), 0.85, 1e-10), vec![]);
+    }
+
+    #[test]
+    fn test_degree_centrality() {
+        let graph: Graph = star(5).build();
+        close(
+            &degree_centrality(&graph),
+            &[(0, 1.0), (1, 0.25), (2, 0.25), (3, 0.25), (4, 0.25)],
+        );
+        let mut graph = Graph::new();
+        graph.add_directed_edge(1, 0);
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(2, 1);
+        close(&in_degree_centrality(&graph), &[(0, 1.0), (1, 0.5), (2, 0.0)]);
+        close(&degree_centrality(&graph), &[(2, 1.0), (1, 0.5), (0, 0.0)]);
+    }
+
+    #[test]
+    fn test_closeness_centrality() {
+        // 0 <--> 1 <--> 2 <--> 3 <--> 4
+        let graph: Graph = path(5, false).build();
+        close(
+            &closeness_centrality(&graph),
+            &[
+                (2, 4.0 / 6.0),
+                (1, 4.0 / 7.0),
+                (3, 4.0 / 7.0),
+                (0, 0.4),
+                (4, 0.4),
+            ],
+        );
+        // only reaching one of the three other nodes
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_node(2);
+        graph.add_node(3);
+        close(
+            &closeness_centrality(&graph),
+            &[(0, 1.0 / 3.0), (1, 1.0 / 3.0), (2, 0.0), (3, 0.0)],
+        );
+    }
+
+    #[test]
+    fn test_betweenness_centrality() {
+        let graph: Graph = path(5, false).build();
+        close(
+            &betweenness_centrality(&graph),
+            &[(2, 4.0 / 6.0), (1, 0.5), (3, 0.5), (0, 0.0), (4, 0.0)],
+        );
+        // half of the shortest paths between opposite corners
+        // go through each of the other two
+        let graph: Graph = cycle(4, false).build();
+        let sixth = 1.0 / 6.0;
+        close(
+            &betweenness_centrality(&graph),
+            &[(0, sixth), (1, sixth), (2, sixth), (3, sixth)],
+        );
+        let graph = CsrGraph::from(&star(6).build::<Graph>());
+        assert_eq!(betweenness_centrality(&graph)[0], (0, 1.0));
+        // directed: only 1 is in the middle of anything
+        let graph: Graph = path(3, true).build();
+        close(
+            &betweenness_centrality(&graph),
+            &[(1, 0.5), (0, 0.0), (2, 0.0)],
+        );
+    }
+
+    #[test]
+    fn test_core_numbers() {
+        let mut graph: Graph = complete(4, false).build();
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 6);
+        graph.add_undirected_edge(6, 4);
+        graph.add_undirected_edge(6, 7);
+        graph.add_node(8);
+        // (0 1 2 3 all joined up) 3 <--> 4 <--> 5
+        //                                ^      ^
+        //                                |      |
+        //                                v      v
+        //                         7 <--> 6 <----
+        assert_eq!(
+            core_numbers(&graph),
+            vec![
+                (0, 3),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+                (4, 2),
+                (5, 2),
+                (6, 2),
+                (7, 1),
+                (8, 0)
+            ]
+        );
+        assert_eq!(k_core(&graph, 2), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(k_core(&graph, 4), vec![]);
+
+        for seed in 0..20 {
+            let graph: Graph = random_tree(30, seed).build();
+            assert!(core_numbers(&graph).iter().all(|(_, core)| *core == 1));
+        }
+        let graph: Graph = grid(4, 4).build();
+        assert!(core_numbers(&graph).iter().all(|(_, core)| *core == 2));
+    }
+}
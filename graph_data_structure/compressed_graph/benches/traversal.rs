@@ -0,0 +1,94 @@
+//! Runs the traversals on the same large random graph stored
+//! as a `HashMap` adjacency list and as a `CsrGraph`.
+//!
+//! Run with `cargo bench`. The graph size can be changed with
+//! the `NODES` and `EDGES_PER_NODE` environment variables,
+//! the default is a million nodes and four million edges.
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use compressed_graph::{
+    breadth_first_search, connected_components_count, depth_first_search,
+    shortest_path, Adjacency, CsrGraph, Graph,
+};
+use graph_generators::Rng;
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let now = Instant::now();
+    let result = f();
+    (result, now.elapsed())
+}
+
+/// Runs every traversal once, returning something to compare
+/// between the two graphs and the time each one took
+fn run<G: Adjacency>(
+    graph: &G,
+    pairs: &[(usize, usize)],
+) -> (Vec<usize>, [Duration; 4]) {
+    let (bfs, bfs_time) = time(|| breadth_first_search(graph, 0).len());
+    let (dfs, dfs_time) = time(|| depth_first_search(graph, 0).len());
+    let (count, count_time) = time(|| connected_components_count(graph));
+    let (lengths, path_time) = time(|| {
+        pairs
+            .iter()
+            .map(|(start, dest)| {
+                shortest_path(graph, *start, *dest).unwrap_or(usize::MAX)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut summary = vec![bfs, dfs, count];
+    summary.extend(lengths);
+    (summary, [bfs_time, dfs_time, count_time, path_time])
+}
+
+fn main() {
+    let nodes = env_or("NODES", 1_000_000);
+    let edges_per_node = env_or("EDGES_PER_NODE", 4);
+    let queries = env_or("QUERIES", 20);
+    let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+    let (graph, build_time) = time(|| {
+        let mut graph = Graph::new();
+        for node in 0..nodes {
+            graph.add_node(node);
+        }
+        for node in 0..nodes {
+            for _ in 0..edges_per_node {
+                graph.add_undirected_edge(node, rng.below(nodes));
+            }
+        }
+        graph
+    });
+    let (csr, convert_time) = time(|| CsrGraph::from(&graph));
+    let pairs: Vec<(usize, usize)> =
+        (0..queries).map(|_| (rng.below(nodes), rng.below(nodes))).collect();
+
+    let (hash_summary, hash_times) = run(&graph, &pairs);
+    let (csr_summary, csr_times) = run(&csr, &pairs);
+    assert_eq!(hash_summary, csr_summary);
+
+    println!(
+        "{} nodes, {} edges, {} shortest path queries",
+        nodes,
+        nodes * edges_per_node,
+        queries
+    );
+    println!("building the adjacency list: {:>10.2?}", build_time);
+    println!("converting to CSR:           {:>10.2?}", convert_time);
+    println!();
+    println!("{:<22} {:>12} {:>12}", "", "HashMap", "CSR");
+    let names = ["BFS", "DFS", "components", "shortest paths"];
+    for (i, name) in names.iter().enumerate() {
+        println!(
+            "{:<22} {:>12.2?} {:>12.2?}",
+            name, hash_times[i], csr_times[i]
+        );
+    }
+}
@@ -0,0 +1,644 @@
+use std::collections::{HashMap, VecDeque};
+
+pub type NodeId = usize;
+
+/// What the traversals need from a graph. Per-node state is
+/// kept in a `Vec` indexed by node rather than in a
+/// `HashMap`, so node ids are expected to be reasonably
+/// dense.
+pub trait Adjacency {
+    /// One more than the largest node
+    fn node_bound(&self) -> usize;
+
+    fn contains(&self, node: NodeId) -> bool;
+
+    /// The nodes `node` has an edge to, empty if `node` isn't
+    /// in the graph
+    fn neighbours(&self, node: NodeId) -> &[NodeId];
+
+    /// All the nodes of the graph in ascending order
+    fn nodes(&self) -> Vec<NodeId> {
+        (0..self.node_bound()).filter(|node| self.contains(*node)).collect()
+    }
+}
+
+/// What `Graph` does with edges it already has and edges from
+/// a node to itself. An edge that isn't allowed is simply not
+/// added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdgePolicy {
+    pub allow_parallel_edges: bool,
+    pub allow_self_loops: bool,
+}
+
+impl EdgePolicy {
+    /// Anything goes, what `Graph::new` uses
+    pub const MULTIGRAPH: Self =
+        Self { allow_parallel_edges: true, allow_self_loops: true };
+    /// At most one edge u --> v and none u --> u
+    pub const SIMPLE: Self =
+        Self { allow_parallel_edges: false, allow_self_loops: false };
+}
+
+impl Default for EdgePolicy {
+    fn default() -> Self {
+        Self::MULTIGRAPH
+    }
+}
+
+/// The usual adjacency list, cheap to change.
+///
+/// An undirected edge is a directed edge each way, and taking
+/// one out takes out both. Finding the edges into a node
+/// means going through every edge, so `in_degree` and
+/// `remove_node` take O(e) time.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+    policy: EdgePolicy,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::with_policy(EdgePolicy::MULTIGRAPH)
+    }
+
+    pub fn with_policy(policy: EdgePolicy) -> Self {
+        Self { adjacency_list: HashMap::new(), policy }
+    }
+
+    pub fn policy(&self) -> EdgePolicy {
+        self.policy
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    fn allows(&self, u: NodeId, v: NodeId) -> bool {
+        (u != v || self.policy.allow_self_loops)
+            && (self.policy.allow_parallel_edges || !self.has_edge(u, v))
+    }
+
+    // Use this method to add directed edge u --> v. Returns
+    // whether the policy let it in.
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) -> bool {
+        if !self.allows(u, v) {
+            return false;
+        }
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.add_node(v);
+        true
+    }
+
+    // add a bi-directional edge u <--> v. A self loop u <--> u
+    // goes into the list of u twice. Adds nothing unless the
+    // policy lets in both directions.
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) -> bool {
+        if !self.allows(u, v) || !self.allows(v, u) {
+            return false;
+        }
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+        true
+    }
+
+    /// Takes out `node` and every edge into or out of it.
+    /// Returns whether it was there.
+    pub fn remove_node(&mut self, node: NodeId) -> bool {
+        if self.adjacency_list.remove(&node).is_none() {
+            return false;
+        }
+        for neighbours in self.adjacency_list.values_mut() {
+            neighbours.retain(|neighbour| *neighbour != node);
+        }
+        true
+    }
+
+    /// Takes out one edge u --> v, returns whether there was
+    /// one
+    pub fn remove_directed_edge(&mut self, u: NodeId, v: NodeId) -> bool {
+        let Some(neighbours) = self.adjacency_list.get_mut(&u) else {
+            return false;
+        };
+        match neighbours.iter().position(|neighbour| *neighbour == v) {
+            Some(i) => {
+                neighbours.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes out one edge u <--> v, returns whether there was
+    /// one
+    pub fn remove_undirected_edge(&mut self, u: NodeId, v: NodeId) -> bool {
+        if !self.has_edge(u, v) || !self.has_edge(v, u) {
+            return false;
+        }
+        self.remove_directed_edge(u, v);
+        self.remove_directed_edge(v, u);
+        true
+    }
+
+    pub fn has_edge(&self, u: NodeId, v: NodeId) -> bool {
+        self.neighbours(u).contains(&v)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency_list.len()
+    }
+
+    /// Number of edges, undirected edges counting twice
+    pub fn edge_count(&self) -> usize {
+        self.adjacency_list.values().map(Vec::len).sum()
+    }
+
+    pub fn out_degree(&self, node: NodeId) -> usize {
+        self.neighbours(node).len()
+    }
+
+    pub fn in_degree(&self, node: NodeId) -> usize {
+        self.adjacency_list.values().flatten().filter(|v| **v == node).count()
+    }
+
+    /// The nodes in no particular order, `nodes` sorts them
+    pub fn node_iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.adjacency_list.keys().copied()
+    }
+
+    /// Every edge u --> v, by ascending u and then in the
+    /// order they were added. Undirected edges show up both
+    /// ways.
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.nodes()
+            .into_iter()
+            .flat_map(move |u| self.neighbours(u).iter().map(move |v| (u, *v)))
+    }
+
+    /// The same graph with every edge pointing the other way
+    pub fn reverse(&self) -> Self {
+        let mut reverse = Self::with_policy(self.policy);
+        for node in self.node_iter() {
+            reverse.add_node(node);
+        }
+        // the edges already follow the policy, no need to
+        // check them again
+        for (u, v) in self.edges() {
+            reverse.adjacency_list.entry(v).or_default().push(u);
+        }
+        reverse
+    }
+
+    /// Just the given nodes, the ones in the graph anyway, and
+    /// the edges between them
+    pub fn induced_subgraph(
+        &self,
+        nodes: impl IntoIterator<Item = NodeId>,
+    ) -> Self {
+        let mut subgraph = Self::with_policy(self.policy);
+        for node in nodes {
+            if self.contains(node) {
+                subgraph.add_node(node);
+            }
+        }
+        for (u, v) in self.edges() {
+            if subgraph.contains(u) && subgraph.contains(v) {
+                subgraph.adjacency_list.get_mut(&u).unwrap().push(v);
+            }
+        }
+        subgraph
+    }
+}
+
+impl Adjacency for Graph {
+    fn node_bound(&self) -> usize {
+        self.adjacency_list.keys().max().map_or(0, |node| node + 1)
+    }
+
+    fn contains(&self, node: NodeId) -> bool {
+        self.adjacency_list.contains_key(&node)
+    }
+
+    fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.adjacency_list.get(&node).map_or(&[], |n| n.as_slice())
+    }
+
+    fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+}
+
+/// So the generated test graphs can be built straight into a
+/// `Graph` by the crates built on this one, which enable the
+/// `generators` feature from their dev-dependencies
+#[cfg(feature = "generators")]
+impl graph_generators::BuildGraph for Graph {
+    fn add_node(&mut self, node_id: NodeId) {
+        Graph::add_node(self, node_id);
+    }
+
+    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        Graph::add_directed_edge(self, u, v);
+    }
+
+    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        Graph::add_undirected_edge(self, u, v);
+    }
+}
+
+/// Compressed sparse row graph.
+///
+/// The neighbours of every node sit next to each other in a
+/// single `targets` array, node `n` owning the slice
+/// `targets[offsets[n]..offsets[n + 1]]`. Looking up the
+/// neighbours is two array reads instead of a hash, and a
+/// traversal walks through memory mostly in order. The price
+/// is that the graph can't be changed once built.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CsrGraph {
+    offsets: Vec<usize>,
+    targets: Vec<NodeId>,
+    // ids below the bound that aren't nodes of the graph
+    // just get no neighbours
+    present: Vec<bool>,
+}
+
+impl CsrGraph {
+    /// Number of edges, undirected edges counting twice
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+}
+
+impl From<&Graph> for CsrGraph {
+    fn from(graph: &Graph) -> Self {
+        let bound = graph.node_bound();
+        let mut offsets = Vec::with_capacity(bound + 1);
+        let mut targets = Vec::new();
+        let mut present = vec![false; bound];
+        offsets.push(0);
+        for (node, present) in present.iter_mut().enumerate() {
+            if let Some(neighbours) = graph.adjacency_list.get(&node) {
+                *present = true;
+                targets.extend_from_slice(neighbours);
+            }
+            offsets.push(targets.len());
+        }
+        Self { offsets, targets, present }
+    }
+}
+
+impl Adjacency for CsrGraph {
+    fn node_bound(&self) -> usize {
+        self.present.len()
+    }
+
+    fn contains(&self, node: NodeId) -> bool {
+        self.present.get(node).copied().unwrap_or(false)
+    }
+
+    fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        if node >= self.present.len() {
+            return &[];
+        }
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+// The traversals below run on anything `Adjacency`. The
+// crates `breadth_first_search`, `depth_first_search`,
+// `connected_components_count`, `largest_component` and
+// `shortest_path` keep their own `HashMap` versions, which
+// give the same answers and are kept as simple examples.
+
+/// Breadth-first search algorithm, the nodes reachable from
+/// `start_node` in the order they are visited
+pub fn breadth_first_search<G: Adjacency>(
+    graph: &G,
+    start_node: NodeId,
+) -> Vec<NodeId> {
+    if !graph.contains(start_node) {
+        return Vec::new();
+    }
+    let mut visited = vec![false; graph.node_bound()];
+    let mut traversal = Vec::new();
+    let mut queue = VecDeque::new();
+    visited[start_node] = true;
+    queue.push_back(start_node);
+    while let Some(current_node) = queue.pop_front() {
+        traversal.push(current_node);
+        for neighbour in graph.neighbours(current_node) {
+            if !visited[*neighbour] {
+                visited[*neighbour] = true;
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+    traversal
+}
+
+/// Depth-first search algorithm, visiting nodes in the same
+/// order as the recursive version in `depth_first_search`.
+/// With millions of nodes a path can get deep enough to
+/// overflow the call stack, so this one keeps its own stack,
+/// pushing the neighbours in reverse so the first one comes
+/// off first.
+pub fn depth_first_search<G: Adjacency>(
+    graph: &G,
+    start_node: NodeId,
+) -> Vec<NodeId> {
+    if !graph.contains(start_node) {
+        return Vec::new();
+    }
+    let mut visited = vec![false; graph.node_bound()];
+    let mut traversal = Vec::new();
+    let mut stack = vec![start_node];
+    while let Some(current_node) = stack.pop() {
+        if visited[current_node] {
+            continue;
+        }
+        visited[current_node] = true;
+        traversal.push(current_node);
+        for neighbour in graph.neighbours(current_node).iter().rev() {
+            if !visited[*neighbour] {
+                stack.push(*neighbour);
+            }
+        }
+    }
+    traversal
+}
+
+/// The connected components of an undirected graph, each
+/// sorted, in the order of their smallest node
+pub fn connected_components<G: Adjacency>(graph: &G) -> Vec<Vec<NodeId>> {
+    let mut visited = vec![false; graph.node_bound()];
+    let mut components = Vec::new();
+    let mut queue = VecDeque::new();
+    for start in graph.nodes() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        queue.push_back(start);
+        let mut component = Vec::new();
+        while let Some(current_node) = queue.pop_front() {
+            component.push(current_node);
+            for neighbour in graph.neighbours(current_node) {
+                if !visited[*neighbour] {
+                    visited[*neighbour] = true;
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+    components
+}
+
+pub fn connected_components_count<G: Adjacency>(graph: &G) -> usize {
+    connected_components(graph).len()
+}
+
+/// Number of nodes in the largest connected component
+pub fn largest_component<G: Adjacency>(graph: &G) -> usize {
+    connected_components(graph).iter().map(Vec::len).max().unwrap_or(0)
+}
+
+/// The nodes on a shortest path from `start` to `dest`,
+/// both included, found with a BFS. `None` if `dest` can't
+/// be reached.
+pub fn shortest_path_to<G: Adjacency>(
+    graph: &G,
+    start: NodeId,
+    dest: NodeId,
+) -> Option<Vec<NodeId>> {
+    if !graph.contains(start) || !graph.contains(dest) {
+        return None;
+    }
+    // the start node is its own parent
+    let mut parent = vec![None; graph.node_bound()];
+    let mut queue = VecDeque::new();
+    parent[start] = Some(start);
+    queue.push_back(start);
+    while let Some(current_node) = queue.pop_front() {
+        if current_node == dest {
+            let mut path = vec![dest];
+            let mut node = dest;
+            while node != start {
+                node = parent[node].unwrap();
+                path.push(node);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for neighbour in graph.neighbours(current_node) {
+            if parent[*neighbour].is_none() {
+                parent[*neighbour] = Some(current_node);
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+    None
+}
+
+/// Number of edges on a shortest path from `start` to `dest`
+pub fn shortest_path<G: Adjacency>(
+    graph: &G,
+    start: NodeId,
+    dest: NodeId,
+) -> Option<usize> {
+    shortest_path_to(graph, start, dest).map(|path| path.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(6, 7);
+        graph.add_node(9);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      v
+        // 2 <--> 3 <--> 4    6 <--> 7    9
+        graph
+    }
+
+    #[test]
+    fn test_csr_graph() {
+        let graph = example_graph();
+        let csr = CsrGraph::from(&graph);
+        assert_eq!(csr.node_bound(), 10);
+        assert_eq!(csr.nodes(), vec![0, 1, 2, 3, 4, 6, 7, 9]);
+        assert_eq!(csr.nodes(), graph.nodes());
+        assert_eq!(csr.edge_count(), 12);
+        assert!(!csr.contains(5));
+        assert!(!csr.contains(10));
+        for node in 0..12 {
+            assert_eq!(csr.neighbours(node), graph.neighbours(node));
+        }
+        assert_eq!(CsrGraph::from(&Graph::new()).nodes(), vec![]);
+    }
+
+    #[test]
+    fn test_traversals() {
+        let graph = example_graph();
+        let csr = CsrGraph::from(&graph);
+        assert_eq!(breadth_first_search(&graph, 0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(depth_first_search(&graph, 0), vec![0, 1, 3, 2, 4]);
+        assert_eq!(breadth_first_search(&csr, 0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(depth_first_search(&csr, 0), vec![0, 1, 3, 2, 4]);
+        assert_eq!(depth_first_search(&csr, 9), vec![9]);
+        assert_eq!(breadth_first_search(&csr, 5), vec![]);
+    }
+
+    #[test]
+    fn test_directed_dfs() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(2, 4);
+        graph.add_directed_edge(3, 5);
+        graph.add_directed_edge(5, 2);
+        // 0 ---> 1 ---> 3 ---> 5
+        // |                    |
+        // v                    |
+        // 2 <------------------
+        // |
+        // v
+        // 4
+        let csr = CsrGraph::from(&graph);
+        assert_eq!(depth_first_search(&csr, 0), vec![0, 1, 3, 5, 2, 4]);
+        assert_eq!(breadth_first_search(&csr, 0), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(shortest_path_to(&csr, 3, 4), Some(vec![3, 5, 2, 4]));
+        assert_eq!(shortest_path(&csr, 4, 3), None);
+    }
+
+    #[test]
+    fn test_components() {
+        let graph = example_graph();
+        let csr = CsrGraph::from(&graph);
+        let expected = vec![vec![0, 1, 2, 3, 4], vec![6, 7], vec![9]];
+        assert_eq!(connected_components(&graph), expected);
+        assert_eq!(connected_components(&csr), expected);
+        assert_eq!(connected_components_count(&csr), 3);
+        assert_eq!(largest_component(&csr), 5);
+        assert_eq!(largest_component(&Graph::new()), 0);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let csr = CsrGraph::from(&example_graph());
+        assert_eq!(shortest_path_to(&csr, 0, 4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(shortest_path(&csr, 4, 0), Some(3));
+        assert_eq!(shortest_path(&csr, 7, 7), Some(0));
+        assert_eq!(shortest_path(&csr, 0, 7), None);
+        assert_eq!(shortest_path(&csr, 0, 5), None);
+    }
+
+    #[test]
+    fn test_mutation() {
+        let mut graph = example_graph();
+        assert!(graph.has_edge(1, 3) && graph.has_edge(3, 1));
+        assert_eq!((graph.node_count(), graph.edge_count()), (8, 12));
+        assert_eq!((graph.out_degree(3), graph.in_degree(3)), (3, 3));
+
+        assert!(graph.remove_undirected_edge(1, 3));
+        assert!(!graph.has_edge(1, 3) && !graph.has_edge(3, 1));
+        assert!(!graph.remove_undirected_edge(1, 3));
+        assert!(graph.remove_directed_edge(3, 4));
+        assert!(!graph.remove_directed_edge(3, 4));
+        assert_eq!((graph.out_degree(3), graph.in_degree(3)), (1, 2));
+        assert_eq!(graph.neighbours(4), &[3]);
+
+        assert!(graph.remove_node(0));
+        assert!(!graph.remove_node(0));
+        assert_eq!(graph.nodes(), vec![1, 2, 3, 4, 6, 7, 9]);
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            vec![(2, 3), (3, 2), (4, 3), (6, 7), (7, 6)]
+        );
+        let mut nodes: Vec<NodeId> = graph.node_iter().collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, graph.nodes());
+    }
+
+    #[test]
+    fn test_edge_policy() {
+        let mut graph = Graph::new();
+        assert!(graph.add_directed_edge(0, 1));
+        assert!(graph.add_directed_edge(0, 1));
+        assert!(graph.add_undirected_edge(2, 2));
+        assert_eq!(graph.neighbours(0), &[1, 1]);
+        assert_eq!(graph.neighbours(2), &[2, 2]);
+        assert_eq!(graph.policy(), EdgePolicy::MULTIGRAPH);
+
+        let mut graph = Graph::with_policy(EdgePolicy::SIMPLE);
+        assert!(graph.add_directed_edge(0, 1));
+        assert!(!graph.add_directed_edge(0, 1));
+        assert!(graph.add_directed_edge(1, 0));
+        assert!(!graph.add_undirected_edge(0, 1));
+        assert!(!graph.add_undirected_edge(2, 2));
+        assert!(!graph.contains(2));
+        assert_eq!(graph.edge_count(), 2);
+        // only 5 --> 4 is there, 4 --> 5 alone would be fine
+        assert!(graph.add_directed_edge(5, 4));
+        assert!(!graph.add_undirected_edge(4, 5));
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            [(0, 1), (1, 0), (5, 4)]
+        );
+
+        let mut graph = Graph::with_policy(EdgePolicy {
+            allow_parallel_edges: true,
+            allow_self_loops: false,
+        });
+        assert!(!graph.add_directed_edge(3, 3));
+        assert!(graph.add_undirected_edge(3, 4));
+        assert!(graph.add_undirected_edge(3, 4));
+        assert_eq!(graph.neighbours(4), &[3, 3]);
+    }
+
+    #[test]
+    fn test_reverse_and_subgraph() {
+        let mut graph = Graph::with_policy(EdgePolicy::SIMPLE);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_node(4);
+        // 0 ---> 1
+        // |      |
+        // v      |
+        // 2 <----
+        // |
+        // v
+        // 3      4
+        let reverse = graph.reverse();
+        assert_eq!(reverse.policy(), EdgePolicy::SIMPLE);
+        assert_eq!(reverse.nodes(), graph.nodes());
+        assert_eq!(
+            reverse.edges().collect::<Vec<_>>(),
+            vec![(1, 0), (2, 0), (2, 1), (3, 2)]
+        );
+        assert_eq!(depth_first_search(&reverse, 3), vec![3, 2, 0, 1]);
+
+        let subgraph = graph.induced_subgraph([1, 2, 3, 7]);
+        assert_eq!(subgraph.nodes(), vec![1, 2, 3]);
+        assert_eq!(subgraph.edges().collect::<Vec<_>>(), vec![(1, 2), (2, 3)]);
+        assert_eq!(subgraph.in_degree(2), 1);
+    }
+}
@@ -1,7 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    usize,
-};
+use std::collections::{HashMap, HashSet};
 
 pub type NodeId = usize;
 
@@ -11,30 +8,36 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self { adjacency_list: HashMap::new() }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Takes in an undirected graph and returns
 /// the number of connected components within
 /// the graph
-fn connected_components_count(graph: Graph) -> u32 {
+pub fn connected_components_count(graph: Graph) -> u32 {
     let mut visited = HashSet::new();
     let mut count = 0u32;
     for node in graph.adjacency_list.keys() {
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use topological_sort::{kahn, Graph};
+
+pub use topological_sort::{Cycle, NodeId};
+
+pub type Duration = u64;
+
+/// Tasks that take some time and the order they have to
+/// happen in
+#[derive(Clone, Debug, Default)]
+pub struct Project {
+    graph: Graph,
+    duration: HashMap<NodeId, Duration>,
+    // the longest of any parallel edges
+    lag: HashMap<(NodeId, NodeId), Duration>,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a task, or changes how long an existing one takes.
+    /// Tasks only mentioned in a dependency take no time.
+    pub fn add_task(&mut self, task: NodeId, duration: Duration) {
+        self.graph.add_node(task);
+        self.duration.insert(task, duration);
+    }
+
+    // Use this method to add directed edge before --> after:
+    // `after` can only start `lag` after `before` finishes
+    pub fn add_dependency(
+        &mut self,
+        before: NodeId,
+        after: NodeId,
+        lag: Duration,
+    ) {
+        match self.lag.get_mut(&(before, after)) {
+            Some(known) => *known = (*known).max(lag),
+            None => {
+                self.lag.insert((before, after), lag);
+                self.graph.add_directed_edge(before, after);
+            }
+        }
+    }
+
+    pub fn tasks(&self) -> Vec<NodeId> {
+        self.graph.nodes()
+    }
+
+    fn duration(&self, task: NodeId) -> Duration {
+        self.duration.get(&task).copied().unwrap_or(0)
+    }
+}
+
+/// When a task can happen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    pub duration: Duration,
+    pub earliest_start: Duration,
+    pub earliest_finish: Duration,
+    pub latest_start: Duration,
+    pub latest_finish: Duration,
+    /// How long the task can be delayed without delaying the
+    /// whole project, zero for tasks on a critical path
+    pub slack: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    /// The time it takes to finish every task
+    pub duration: Duration,
+    pub timings: HashMap<NodeId, Timing>,
+    /// A chain of tasks with no slack from the start to the
+    /// end of the project, each one starting as soon as the
+    /// one before it allows. Where there are several the
+    /// smallest tasks are picked.
+    pub critical_path: Vec<NodeId>,
+}
+
+impl Schedule {
+    pub fn slack(&self, task: NodeId) -> Option<Duration> {
+        self.timings.get(&task).map(|timing| timing.slack)
+    }
+
+    /// Every task with no slack, sorted
+    pub fn critical_tasks(&self) -> Vec<NodeId> {
+        let mut tasks: Vec<NodeId> = self
+            .timings
+            .iter()
+            .filter(|(_, timing)| timing.slack == 0)
+            .map(|(task, _)| *task)
+            .collect();
+        tasks.sort_unstable();
+        tasks
+    }
+}
+
+/// Critical path method.
+///
+/// Unlike `longest_path` this weighs tasks and dependencies
+/// by how long they take and checks for cycles first, which
+/// would otherwise have nothing finish before they start.
+///
+/// 1. Going forwards in topological order a task starts as
+///    early as the latest of its dependencies allows.
+/// 2. Going backwards a task finishes as late as the earliest
+///    of the tasks depending on it allows, the tasks nothing
+///    depends on finish with the project.
+///
+/// The difference between the two is the slack of a task.
+pub fn critical_path(project: &Project) -> Result<Schedule, Cycle> {
+    let order = kahn(&project.graph)?;
+
+    let mut earliest_start: HashMap<NodeId, Duration> = HashMap::new();
+    for task in &order {
+        let earliest_finish = earliest_start.get(task).copied().unwrap_or(0)
+            + project.duration(*task);
+        for next in project.graph.neighbours(*task) {
+            let start = earliest_finish + project.lag[&(*task, *next)];
+            let known = earliest_start.entry(*next).or_default();
+            *known = (*known).max(start);
+        }
+        earliest_start.entry(*task).or_default();
+    }
+
+    let duration = order
+        .iter()
+        .map(|task| earliest_start[task] + project.duration(*task))
+        .max()
+        .unwrap_or(0);
+
+    let mut latest_finish: HashMap<NodeId, Duration> = HashMap::new();
+    for task in order.iter().rev() {
+        let finish = project
+            .graph
+            .neighbours(*task)
+            .iter()
+            .map(|next| {
+                latest_finish[next]
+                    - project.duration(*next)
+                    - project.lag[&(*task, *next)]
+            })
+            .min()
+            .unwrap_or(duration);
+        latest_finish.insert(*task, finish);
+    }
+
+    let timings: HashMap<NodeId, Timing> = order
+        .iter()
+        .map(|task| {
+            let duration = project.duration(*task);
+            let timing = Timing {
+                duration,
+                earliest_start: earliest_start[task],
+                earliest_finish: earliest_start[task] + duration,
+                latest_start: latest_finish[task] - duration,
+                latest_finish: latest_finish[task],
+                slack: latest_finish[task] - duration - earliest_start[task],
+            };
+            (*task, timing)
+        })
+        .collect();
+
+    let critical_path = follow_critical_path(project, &timings);
+    Ok(Schedule { duration, timings, critical_path })
+}
+
+/// Starts from the smallest critical task that starts right
+/// away and keeps going to the smallest critical task that
+/// can start as soon as the current one is done. Such a task
+/// exists until we reach the end of the project.
+fn follow_critical_path(
+    project: &Project,
+    timings: &HashMap<NodeId, Timing>,
+) -> Vec<NodeId> {
+    let is_critical = |task: &NodeId| timings[task].slack == 0;
+    let mut current_task = project
+        .tasks()
+        .into_iter()
+        .filter(|task| is_critical(task) && timings[task].earliest_start == 0)
+        .min();
+
+    let mut path = Vec::new();
+    while let Some(task) = current_task {
+        path.push(task);
+        current_task = project
+            .graph
+            .neighbours(task)
+            .iter()
+            .copied()
+            .filter(|next| {
+                is_critical(next)
+                    && timings[&task].earliest_finish
+                        + project.lag[&(task, *next)]
+                        == timings[next].earliest_start
+            })
+            .min();
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_project() -> Project {
+        let mut project = Project::new();
+        project.add_task(0, 3);
+        project.add_task(1, 2);
+        project.add_task(2, 4);
+        project.add_task(3, 1);
+        project.add_task(4, 2);
+        project.add_dependency(0, 1, 0);
+        project.add_dependency(0, 2, 0);
+        project.add_dependency(1, 3, 0);
+        project.add_dependency(2, 3, 0);
+        project.add_dependency(3, 4, 0);
+        //       --> 1(2) --
+        //      |           |
+        //      |           v
+        // 0(3) |          3(1) ---> 4(2)
+        //      |           ^
+        //      |           |
+        //       --> 2(4) --
+        project
+    }
+
+    #[test]
+    fn test_critical_path_00() {
+        let schedule = critical_path(&example_project()).unwrap();
+        assert_eq!(schedule.duration, 10);
+        assert_eq!(schedule.critical_path, vec![0, 2, 3, 4]);
+        assert_eq!(schedule.critical_tasks(), vec![0, 2, 3, 4]);
+        assert_eq!(schedule.slack(1), Some(2));
+        assert_eq!(schedule.slack(7), None);
+        assert_eq!(
+            schedule.timings[&1],
+            Timing {
+                duration: 2,
+                earliest_start: 3,
+                earliest_finish: 5,
+                latest_start: 5,
+                latest_finish: 7,
+                slack: 2,
+            }
+        );
+        assert_eq!(schedule.timings[&4].latest_finish, 10);
+    }
+
+    #[test]
+    fn test_critical_path_01() {
+        // waiting 3 after task 1 makes it critical instead
+        let mut project = example_project();
+        project.add_dependency(1, 3, 3);
+        let schedule = critical_path(&project).unwrap();
+        assert_eq!(schedule.duration, 11);
+        assert_eq!(schedule.critical_path, vec![0, 1, 3, 4]);
+        assert_eq!(schedule.slack(2), Some(1));
+
+        // a shorter parallel dependency changes nothing
+        project.add_dependency(1, 3, 1);
+        assert_eq!(critical_path(&project).unwrap(), schedule);
+    }
+
+    #[test]
+    fn test_independent_tasks() {
+        let mut project = Project::new();
+        project.add_task(0, 5);
+        project.add_task(1, 5);
+        project.add_task(2, 1);
+        let schedule = critical_path(&project).unwrap();
+        assert_eq!(schedule.duration, 5);
+        // two critical paths of one task, the first one wins
+        assert_eq!(schedule.critical_path, vec![0]);
+        assert_eq!(schedule.critical_tasks(), vec![0, 1]);
+        assert_eq!(schedule.slack(2), Some(4));
+
+        let schedule = critical_path(&Project::new()).unwrap();
+        assert_eq!(schedule.duration, 0);
+        assert!(schedule.critical_path.is_empty());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut project = example_project();
+        project.add_dependency(4, 2, 0);
+        assert_eq!(critical_path(&project), Err(Cycle(vec![3, 4, 2])));
+    }
+
+    #[test]
+    fn test_same_as_longest_path() {
+        // with untimed tasks and dependencies taking one unit
+        // of time, the project takes as long as the longest
+        // path has edges
+        let edges =
+            [(0, 1), (0, 2), (1, 2), (10, 11), (11, 12), (11, 13), (11, 14)];
+        let mut project = Project::new();
+        let mut graph = longest_path::Graph::new();
+        for node in [0, 1, 2, 10, 11, 12, 13, 14] {
+            project.add_task(node, 0);
+            graph.add_node(node);
+        }
+        for (u, v) in edges.into_iter().chain([(12, 13), (13, 14)]) {
+            project.add_dependency(u, v, 1);
+            graph.add_directed_edge(u, v);
+        }
+
+        let schedule = critical_path(&project).unwrap();
+        assert_eq!(
+            Ok(schedule.duration),
+            longest_path::longest_path(graph).map(u64::from)
+        );
+        assert_eq!(schedule.critical_path, vec![10, 11, 12, 13, 14]);
+    }
+}
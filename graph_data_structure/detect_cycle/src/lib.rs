@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
 
 /// This representation is vastly simpler
 /// than the representation used elsewhere in
@@ -12,42 +11,12 @@ type Graph = HashMap<u32, Vec<u32>>;
 /// of a directed graph. The function should return
 /// a boolean indicating whether or not the graph
 /// contains a cycle.
-fn is_cyclic(graph: &Graph) -> bool {
-    let mut visited: HashSet<u32> = HashSet::new();
-    let mut visiting: HashSet<u32> = HashSet::new();
-
-    for node in graph.keys() {
-        if detect_cycle(node, graph, &mut visited, &mut visiting) {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn detect_cycle(
-    node: &u32,
-    graph: &Graph,
-    visited: &mut HashSet<u32>,
-    visiting: &mut HashSet<u32>,
-) -> bool {
-    if visiting.contains(node) {
-        return true;
-    }
-
-    visiting.insert(*node);
-
-    for neighbor in graph.get(node).unwrap() {
-        if detect_cycle(neighbor, graph, visited, visiting) {
-            return true;
-        }
-    }
-    visited.insert(*node);
-    visiting.remove(node);
-    false
+pub fn is_cyclic(graph: &Graph) -> bool {
+    has_cycle::find_cycle(graph).is_some()
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -112,4 +81,13 @@ mod tests {
         let detect_cycle = is_cyclic(&graph);
         assert_eq!(detect_cycle, true);
     }
+
+    #[test]
+    fn test_dfs_05() {
+        let mut graph: HashMap<u32, Vec<u32>> = HashMap::new();
+        graph.insert(1, vec![2, 3]);
+        // 2 and 3 have no entry of their own
+        let detect_cycle = is_cyclic(&graph);
+        assert_eq!(detect_cycle, false);
+    }
 }
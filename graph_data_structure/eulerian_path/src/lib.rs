@@ -0,0 +1,431 @@
+//! Eulerian paths and circuits: a walk using every edge of
+//! the graph exactly once, ending where it started in the
+//! case of a circuit.
+//!
+//! Works on any `Adjacency` graph. Whether its edges are
+//! directed can't be told from the adjacency list alone, so
+//! there is a set of functions for each kind. An undirected
+//! edge is expected in both neighbour lists, the way
+//! `add_undirected_edge` puts it there, and a self loop
+//! `u <--> u` shows up twice in the list of `u`.
+use std::{error::Error, fmt};
+
+pub use compressed_graph::{Adjacency, CsrGraph, Graph, NodeId};
+
+pub type Edge = (NodeId, NodeId);
+
+/// Which kind of Eulerian walk a graph has
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eulerian {
+    /// Can start anywhere with edges and comes back there
+    Circuit,
+    /// Has to run from `start` to `end`
+    Path { start: NodeId, end: NodeId },
+}
+
+/// Why a graph has no Eulerian walk
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotEulerian {
+    /// The edges don't all hang together
+    Disconnected,
+    /// Undirected graph with nodes of odd degree: more than
+    /// two of them, or any at all when asking for a circuit
+    OddDegree(Vec<NodeId>),
+    /// Directed graph with nodes whose out-degree and
+    /// in-degree differ by more than a path start or end can
+    /// explain, or at all when asking for a circuit
+    Unbalanced(Vec<NodeId>),
+}
+
+impl fmt::Display for NotEulerian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotEulerian::Disconnected => {
+                write!(f, "the edges are not all connected")
+            }
+            NotEulerian::OddDegree(nodes) => {
+                write!(f, "nodes {:?} have an odd degree", nodes)
+            }
+            NotEulerian::Unbalanced(nodes) => write!(
+                f,
+                "nodes {:?} have different in- and out-degrees",
+                nodes
+            ),
+        }
+    }
+}
+
+impl Error for NotEulerian {}
+
+/// Checks the degrees of a directed graph: every node needs
+/// as many edges in as out, except for a path which leaves
+/// its start once more than it enters it and the other way
+/// round for its end. Doesn't check that the edges are
+/// connected, the walk itself finds that out.
+pub fn classify_directed<G: Adjacency>(
+    graph: &G,
+) -> Result<Eulerian, NotEulerian> {
+    let mut balance = vec![0i64; graph.node_bound()];
+    for node in graph.nodes() {
+        for next in graph.neighbours(node) {
+            balance[node] += 1;
+            balance[*next] -= 1;
+        }
+    }
+    let unbalanced: Vec<NodeId> =
+        (0..balance.len()).filter(|node| balance[*node] != 0).collect();
+    match unbalanced.as_slice() {
+        [] => Ok(Eulerian::Circuit),
+        [u, v] if balance[*u] * balance[*v] == -1 => {
+            let (start, end) =
+                if balance[*u] == 1 { (*u, *v) } else { (*v, *u) };
+            Ok(Eulerian::Path { start, end })
+        }
+        _ => Err(NotEulerian::Unbalanced(unbalanced)),
+    }
+}
+
+/// Checks the degrees of an undirected graph: a circuit
+/// needs every degree to be even, a path allows exactly two
+/// odd ones at its ends
+pub fn classify_undirected<G: Adjacency>(
+    graph: &G,
+) -> Result<Eulerian, NotEulerian> {
+    let odd: Vec<NodeId> = graph
+        .nodes()
+        .into_iter()
+        .filter(|node| graph.neighbours(*node).len() % 2 == 1)
+        .collect();
+    match odd.as_slice() {
+        [] => Ok(Eulerian::Circuit),
+        [start, end] => Ok(Eulerian::Path { start: *start, end: *end }),
+        _ => Err(NotEulerian::OddDegree(odd)),
+    }
+}
+
+/// The edges of a directed graph in the order an Eulerian
+/// path walks them. A circuit counts as a path, it just
+/// happens to end where it started.
+pub fn directed_eulerian_path<G: Adjacency>(
+    graph: &G,
+) -> Result<Vec<Edge>, NotEulerian> {
+    let start = match classify_directed(graph)? {
+        Eulerian::Circuit => first_with_edges(graph),
+        Eulerian::Path { start, .. } => Some(start),
+    };
+    walk(start, &Edges::directed(graph))
+}
+
+/// The edges of a directed graph in the order an Eulerian
+/// circuit walks them, starting from the smallest node with
+/// an edge
+pub fn directed_eulerian_circuit<G: Adjacency>(
+    graph: &G,
+) -> Result<Vec<Edge>, NotEulerian> {
+    if let Eulerian::Path { start, end } = classify_directed(graph)? {
+        return Err(NotEulerian::Unbalanced(sorted(start, end)));
+    }
+    walk(first_with_edges(graph), &Edges::directed(graph))
+}
+
+/// Same as `directed_eulerian_path` for an undirected graph
+pub fn undirected_eulerian_path<G: Adjacency>(
+    graph: &G,
+) -> Result<Vec<Edge>, NotEulerian> {
+    let start = match classify_undirected(graph)? {
+        Eulerian::Circuit => first_with_edges(graph),
+        Eulerian::Path { start, .. } => Some(start),
+    };
+    walk(start, &Edges::undirected(graph))
+}
+
+/// Same as `directed_eulerian_circuit` for an undirected
+/// graph
+pub fn undirected_eulerian_circuit<G: Adjacency>(
+    graph: &G,
+) -> Result<Vec<Edge>, NotEulerian> {
+    if let Eulerian::Path { start, end } = classify_undirected(graph)? {
+        return Err(NotEulerian::OddDegree(sorted(start, end)));
+    }
+    walk(first_with_edges(graph), &Edges::undirected(graph))
+}
+
+fn sorted(u: NodeId, v: NodeId) -> Vec<NodeId> {
+    vec![u.min(v), u.max(v)]
+}
+
+fn first_with_edges<G: Adjacency>(graph: &G) -> Option<NodeId> {
+    graph.nodes().into_iter().find(|node| !graph.neighbours(*node).is_empty())
+}
+
+/// Every edge numbered, along with the edges each node can
+/// leave through, as `(edge, other end)`. An undirected edge
+/// can be left through from both ends but has just the one
+/// number, so walking it once uses it up.
+struct Edges {
+    edge_count: usize,
+    incidence: Vec<Vec<(usize, NodeId)>>,
+}
+
+impl Edges {
+    fn directed<G: Adjacency>(graph: &G) -> Self {
+        let mut incidence = vec![Vec::new(); graph.node_bound()];
+        let mut edge_count = 0;
+        for node in graph.nodes() {
+            for next in graph.neighbours(node) {
+                incidence[node].push((edge_count, *next));
+                edge_count += 1;
+            }
+        }
+        Self { edge_count, incidence }
+    }
+
+    // `u <--> v` is numbered when it turns up in the list of
+    // the smaller end, a self loop every second time it turns
+    // up, since it's in there twice
+    fn undirected<G: Adjacency>(graph: &G) -> Self {
+        let mut incidence = vec![Vec::new(); graph.node_bound()];
+        let mut edge_count = 0;
+        for node in graph.nodes() {
+            let mut loops = 0;
+            for next in graph.neighbours(node) {
+                if node == *next {
+                    loops += 1;
+                    if loops % 2 == 1 {
+                        continue;
+                    }
+                } else if node > *next {
+                    continue;
+                }
+                incidence[node].push((edge_count, *next));
+                if node != *next {
+                    incidence[*next].push((edge_count, node));
+                }
+                edge_count += 1;
+            }
+        }
+        Self { edge_count, incidence }
+    }
+}
+
+/// Hierholzer's algorithm. Follows unused edges from `start`
+/// until it gets stuck, which can only happen back where the
+/// walk started (or at the end of a path). Then it backs up
+/// to the last node that still has unused edges and splices
+/// in a detour from there. The nodes come off the stack in
+/// reverse order of the walk.
+fn walk(
+    start: Option<NodeId>,
+    edges: &Edges,
+) -> Result<Vec<Edge>, NotEulerian> {
+    let Some(start) = start else {
+        return Ok(Vec::new());
+    };
+    let mut used = vec![false; edges.edge_count];
+    // the next entry of each incidence list to look at
+    let mut next_edge = vec![0; edges.incidence.len()];
+    let mut stack = vec![start];
+    let mut nodes = Vec::with_capacity(edges.edge_count + 1);
+
+    while let Some(node) = stack.last().copied() {
+        let incidence = &edges.incidence[node];
+        while next_edge[node] < incidence.len()
+            && used[incidence[next_edge[node]].0]
+        {
+            next_edge[node] += 1;
+        }
+        match incidence.get(next_edge[node]) {
+            Some((edge, next)) => {
+                used[*edge] = true;
+                stack.push(*next);
+            }
+            None => nodes.push(stack.pop().unwrap()),
+        }
+    }
+
+    // some edges weren't reachable from the start
+    if nodes.len() != edges.edge_count + 1 {
+        return Err(NotEulerian::Disconnected);
+    }
+    nodes.reverse();
+    Ok(nodes.windows(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::{complete, cycle, erdos_renyi, grid, path};
+
+    /// Whether `trail` is a walk through the graph using each
+    /// of `edges` exactly once
+    fn uses_every_edge(trail: &[Edge], edges: &[Edge], directed: bool) {
+        assert_eq!(trail.len(), edges.len());
+        for pair in trail.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+        let normal = |(u, v): Edge| {
+            if directed {
+                (u, v)
+            } else {
+                (u.min(v), u.max(v))
+            }
+        };
+        let mut expected: Vec<Edge> =
+            edges.iter().map(|e| normal(*e)).collect();
+        let mut walked: Vec<Edge> = trail.iter().map(|e| normal(*e)).collect();
+        expected.sort_unstable();
+        walked.sort_unstable();
+        assert_eq!(walked, expected);
+    }
+
+    #[test]
+    fn test_directed_circuit() {
+        let edges = [(0, 1), (1, 2), (2, 0), (0, 3), (3, 4), (4, 0)];
+        let mut graph = Graph::new();
+        for (u, v) in edges {
+            graph.add_directed_edge(u, v);
+        }
+        // 2 <--- 1    4 ---> 0
+        //  \     ^    ^     /
+        //   \    |    |    /
+        //    --> 0 ---> 3 <
+        //
+        // (the 0 on the right is the same node as the one in
+        // the middle)
+        assert_eq!(classify_directed(&graph), Ok(Eulerian::Circuit));
+        let circuit = directed_eulerian_circuit(&graph).unwrap();
+        assert_eq!(
+            circuit,
+            vec![(0, 1), (1, 2), (2, 0), (0, 3), (3, 4), (4, 0)]
+        );
+        uses_every_edge(&circuit, &edges, true);
+        assert_eq!(directed_eulerian_path(&graph), Ok(circuit));
+        assert_eq!(directed_eulerian_circuit(&Graph::new()), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_directed_path() {
+        let edges = [(0, 1), (1, 2), (2, 1), (1, 3), (3, 2)];
+        let mut graph = Graph::new();
+        for (u, v) in edges {
+            graph.add_directed_edge(u, v);
+        }
+        assert_eq!(
+            classify_directed(&graph),
+            Ok(Eulerian::Path { start: 0, end: 2 })
+        );
+        let trail = directed_eulerian_path(&graph).unwrap();
+        uses_every_edge(&trail, &edges, true);
+        assert_eq!(trail[0].0, 0);
+        assert_eq!(
+            directed_eulerian_circuit(&graph),
+            Err(NotEulerian::Unbalanced(vec![0, 2]))
+        );
+
+        graph.add_directed_edge(0, 4);
+        assert_eq!(
+            directed_eulerian_path(&graph),
+            Err(NotEulerian::Unbalanced(vec![0, 2, 4]))
+        );
+    }
+
+    #[test]
+    fn test_konigsberg() {
+        // the seven bridges, four of them parallel pairs
+        let edges = [(0, 1), (0, 1), (0, 2), (0, 2), (0, 3), (1, 3), (2, 3)];
+        let mut graph = Graph::new();
+        for (u, v) in edges {
+            graph.add_undirected_edge(u, v);
+        }
+        let error = undirected_eulerian_path(&graph).unwrap_err();
+        assert_eq!(error, NotEulerian::OddDegree(vec![0, 1, 2, 3]));
+        assert_eq!(error.to_string(), "nodes [0, 1, 2, 3] have an odd degree");
+
+        // one more bridge between 1 and 2 makes a walk possible
+        graph.add_undirected_edge(1, 2);
+        let mut edges = edges.to_vec();
+        edges.push((1, 2));
+        assert_eq!(
+            classify_undirected(&graph),
+            Ok(Eulerian::Path { start: 0, end: 3 })
+        );
+        let trail = undirected_eulerian_path(&graph).unwrap();
+        uses_every_edge(&trail, &edges, false);
+        assert_eq!((trail[0].0, trail[7].1), (0, 3));
+    }
+
+    #[test]
+    fn test_self_loops_and_disconnected() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_node(5);
+        // 0 <--> 1 <--> 2   5
+        //       ( )
+        let trail = undirected_eulerian_path(&graph).unwrap();
+        assert_eq!(trail, vec![(0, 1), (1, 1), (1, 2)]);
+
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 3);
+        assert_eq!(
+            undirected_eulerian_path(&CsrGraph::from(&graph)),
+            Err(NotEulerian::Disconnected)
+        );
+
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 0);
+        graph.add_directed_edge(2, 2);
+        assert_eq!(
+            directed_eulerian_circuit(&graph),
+            Err(NotEulerian::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_generated() {
+        for n in 3..10 {
+            let generated = cycle(n, false);
+            let graph: Graph = generated.build();
+            let circuit = undirected_eulerian_circuit(&graph).unwrap();
+            uses_every_edge(&circuit, &generated.edges, false);
+
+            let generated = path(n, true);
+            let trail = directed_eulerian_path(&generated.build::<Graph>());
+            uses_every_edge(&trail.unwrap(), &generated.edges, true);
+        }
+        // every degree is even in a complete graph with an odd
+        // number of nodes, and every node balanced in a
+        // directed one
+        let generated = complete(7, false);
+        let circuit = undirected_eulerian_circuit(&generated.build::<Graph>());
+        uses_every_edge(&circuit.unwrap(), &generated.edges, false);
+        let generated = complete(6, true);
+        let circuit = directed_eulerian_circuit(&generated.build::<Graph>());
+        uses_every_edge(&circuit.unwrap(), &generated.edges, true);
+        assert!(matches!(
+            undirected_eulerian_path(&grid(3, 3).build::<Graph>()),
+            Err(NotEulerian::OddDegree(nodes)) if nodes == [1, 3, 5, 7]
+        ));
+
+        // whenever a random graph does have a walk, it has to
+        // use every edge once
+        let mut found = 0;
+        for seed in 0..300 {
+            let generated = erdos_renyi(8, 0.3, seed % 2 == 0, seed);
+            let graph: Graph = generated.build();
+            let trail = if generated.directed {
+                directed_eulerian_path(&graph)
+            } else {
+                undirected_eulerian_path(&graph)
+            };
+            if let Ok(trail) = trail {
+                uses_every_edge(&trail, &generated.edges, generated.directed);
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub type NodeId = usize;
+pub type Colour = usize;
+
+/// An undirected graph
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // add a bi-directional edge u <--> v
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.adjacency_list.get(&node).map_or(&[], |n| n.as_slice())
+    }
+
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.neighbours(node).len()
+    }
+}
+
+/// A cycle with an odd number of nodes, in the order the
+/// edges run. No graph containing one can be two-coloured.
+/// A self-loop is an odd cycle of a single node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OddCycle(pub Vec<NodeId>);
+
+/// Splits the graph into two sides so that every edge runs
+/// from one side to the other, or returns an odd cycle that
+/// shows it can't be done.
+///
+/// A BFS from every uncoloured node gives its neighbours the
+/// opposite colour. An edge between two nodes of the same
+/// colour closes an odd cycle: both ends are the same
+/// distance from the BFS root, so the two tree paths down to
+/// them plus that edge have an odd number of edges.
+///
+/// Both sides come out sorted, the first one holds the
+/// smallest node of every connected component.
+pub fn two_colouring(
+    graph: &Graph,
+) -> Result<(Vec<NodeId>, Vec<NodeId>), OddCycle> {
+    let mut side = HashMap::new();
+    let mut parent = HashMap::new();
+
+    for start in graph.nodes() {
+        if side.contains_key(&start) {
+            continue;
+        }
+        side.insert(start, false);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(current_node) = queue.pop_front() {
+            for neighbour in graph.neighbours(current_node) {
+                match side.get(neighbour) {
+                    None => {
+                        side.insert(*neighbour, !side[&current_node]);
+                        parent.insert(*neighbour, current_node);
+                        queue.push_back(*neighbour);
+                    }
+                    Some(colour) if *colour == side[&current_node] => {
+                        return Err(odd_cycle(
+                            &parent,
+                            current_node,
+                            *neighbour,
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    for node in graph.nodes() {
+        if side[&node] {
+            second.push(node);
+        } else {
+            first.push(node);
+        }
+    }
+    Ok((first, second))
+}
+
+/// Walks up the BFS tree from both ends of the edge `u <--> v`
+/// until the paths meet
+fn odd_cycle(
+    parent: &HashMap<NodeId, NodeId>,
+    u: NodeId,
+    v: NodeId,
+) -> OddCycle {
+    let path_to_root = |mut node: NodeId| {
+        let mut path = vec![node];
+        while let Some(next) = parent.get(&node) {
+            node = *next;
+            path.push(node);
+        }
+        path
+    };
+
+    let mut from_u = path_to_root(u);
+    let mut from_v = path_to_root(v);
+    // drop the shared part above the lowest common ancestor,
+    // keeping the ancestor itself on `from_u`
+    while from_u.len() > 1
+        && from_v.len() > 1
+        && from_u[from_u.len() - 2] == from_v[from_v.len() - 2]
+    {
+        from_u.pop();
+        from_v.pop();
+    }
+    from_v.pop();
+
+    // u ... ancestor, then back down to v
+    from_u.extend(from_v.into_iter().rev());
+    OddCycle(from_u)
+}
+
+pub fn is_bipartite(graph: &Graph) -> bool {
+    two_colouring(graph).is_ok()
+}
+
+/// Whether no edge joins two nodes of the same colour and
+/// every node has a colour
+pub fn is_proper_colouring(
+    graph: &Graph,
+    colouring: &HashMap<NodeId, Colour>,
+) -> bool {
+    graph.nodes().into_iter().all(|node| {
+        colouring.get(&node).is_some_and(|colour| {
+            graph
+                .neighbours(node)
+                .iter()
+                .all(|neighbour| colouring.get(neighbour) != Some(colour))
+        })
+    })
+}
+
+/// Number of different colours used
+pub fn colour_count(colouring: &HashMap<NodeId, Colour>) -> usize {
+    colouring.values().collect::<HashSet<_>>().len()
+}
+
+/// The smallest colour none of the neighbours of `node` has
+fn smallest_free_colour(
+    graph: &Graph,
+    colouring: &HashMap<NodeId, Colour>,
+    node: NodeId,
+) -> Colour {
+    let taken: HashSet<Colour> = graph
+        .neighbours(node)
+        .iter()
+        .filter_map(|neighbour| colouring.get(neighbour).copied())
+        .collect();
+    (0..).find(|colour| !taken.contains(colour)).unwrap()
+}
+
+/// Greedy colouring.
+///
+/// Goes through the nodes in ascending order and gives each
+/// one the smallest colour none of its neighbours has yet.
+/// Never uses more than `max degree + 1` colours but can be
+/// far off the best colouring depending on the order.
+///
+/// Self-loops are ignored, no colouring can satisfy them.
+pub fn greedy_colouring(graph: &Graph) -> HashMap<NodeId, Colour> {
+    let mut colouring = HashMap::new();
+    for node in graph.nodes() {
+        let colour = smallest_free_colour(graph, &colouring, node);
+        colouring.insert(node, colour);
+    }
+    colouring
+}
+
+/// DSATUR (degree of saturation) colouring.
+///
+/// Like the greedy colouring, but always colours next the
+/// node whose neighbours already use the most different
+/// colours, i.e. the one with the fewest options left. Ties
+/// go to the node with the most neighbours, then to the
+/// smallest node. Colours bipartite graphs, cycles and
+/// wheels optimally.
+///
+/// Self-loops are ignored, no colouring can satisfy them.
+pub fn dsatur(graph: &Graph) -> HashMap<NodeId, Colour> {
+    let mut colouring = HashMap::new();
+    let mut saturation: HashMap<NodeId, HashSet<Colour>> =
+        graph.nodes().into_iter().map(|node| (node, HashSet::new())).collect();
+
+    while let Some(node) = graph
+        .nodes()
+        .into_iter()
+        .filter(|node| !colouring.contains_key(node))
+        .max_by_key(|node| {
+            (saturation[node].len(), graph.degree(*node), usize::MAX - node)
+        })
+    {
+        let colour = smallest_free_colour(graph, &colouring, node);
+        colouring.insert(node, colour);
+        for neighbour in graph.neighbours(node) {
+            saturation.get_mut(neighbour).unwrap().insert(colour);
+        }
+    }
+    colouring
+}
+
+/// Exact k-colouring by backtracking.
+///
+/// Colours the nodes one after the other, most neighbours
+/// first, undoing the last choice whenever a node has no
+/// colour left. A node never gets a colour more than one
+/// above the highest colour used so far: swapping colour
+/// names around gives the same colouring, so there's no
+/// point trying them all. Still exponential, meant for
+/// small graphs.
+///
+/// Returns `None` if there's no way to colour the graph with
+/// `k` colours, which is always the case for a graph with a
+/// self-loop.
+pub fn k_colouring(
+    graph: &Graph,
+    k: usize,
+) -> Option<HashMap<NodeId, Colour>> {
+    let mut order = graph.nodes();
+    if order.iter().any(|node| graph.neighbours(*node).contains(node)) {
+        return None;
+    }
+    order.sort_by_key(|node| std::cmp::Reverse(graph.degree(*node)));
+
+    let mut colouring = HashMap::new();
+    if backtrack(graph, &order, k, 0, &mut colouring) {
+        Some(colouring)
+    } else {
+        None
+    }
+}
+
+fn backtrack(
+    graph: &Graph,
+    order: &[NodeId],
+    k: usize,
+    colours_used: usize,
+    colouring: &mut HashMap<NodeId, Colour>,
+) -> bool {
+    let Some((node, rest)) = order.split_first() else {
+        return true;
+    };
+
+    for colour in 0..k.min(colours_used + 1) {
+        let clashes = graph
+            .neighbours(*node)
+            .iter()
+            .any(|neighbour| colouring.get(neighbour) == Some(&colour));
+        if clashes {
+            continue;
+        }
+
+        colouring.insert(*node, colour);
+        let colours_used = colours_used.max(colour + 1);
+        if backtrack(graph, rest, k, colours_used, colouring) {
+            return true;
+        }
+        colouring.remove(node);
+    }
+    false
+}
+
+/// The smallest number of colours the graph can be coloured
+/// with, `None` if it has a self-loop. The DSATUR colouring
+/// gives an upper bound, so we only need to try the smaller
+/// numbers of colours.
+pub fn chromatic_number(graph: &Graph) -> Option<usize> {
+    let upper_bound = colour_count(&dsatur(graph));
+    (0..=upper_bound).find(|k| k_colouring(graph, *k).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_odd_cycle(graph: &Graph, cycle: &OddCycle) {
+        assert_eq!(cycle.0.len() % 2, 1);
+        for (i, node) in cycle.0.iter().enumerate() {
+            let next = cycle.0[(i + 1) % cycle.0.len()];
+            assert!(graph.neighbours(*node).contains(&next));
+        }
+    }
+
+    fn cycle_graph(n: usize) -> Graph {
+        let mut graph = Graph::new();
+        for node in 0..n {
+            graph.add_undirected_edge(node, (node + 1) % n);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_bipartite_00() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 0);
+        graph.add_undirected_edge(4, 5);
+        graph.add_node(6);
+        // 0 <--> 1    4 <--> 5
+        // ^      ^
+        // |      |    6
+        // v      v
+        // 3 <--> 2
+        assert_eq!(
+            two_colouring(&graph),
+            Ok((vec![0, 2, 4, 6], vec![1, 3, 5]))
+        );
+        assert!(is_bipartite(&graph));
+    }
+
+    #[test]
+    fn test_bipartite_01() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 1);
+        graph.add_undirected_edge(0, 6);
+        // 6 <--> 0 <--> 1 <--> 2 <--> 3
+        //               ^             ^
+        //               |             |
+        //               v             v
+        //               5 <---------> 4
+        let cycle = two_colouring(&graph).unwrap_err();
+        assert_odd_cycle(&graph, &cycle);
+        assert_eq!(cycle.0.len(), 5);
+        assert!(!is_bipartite(&graph));
+
+        let cycle = two_colouring(&cycle_graph(7)).unwrap_err();
+        assert_odd_cycle(&cycle_graph(7), &cycle);
+        assert_eq!(cycle.0.len(), 7);
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 1);
+        assert_eq!(two_colouring(&graph), Err(OddCycle(vec![1])));
+        assert_eq!(k_colouring(&graph, 3), None);
+        assert_eq!(chromatic_number(&graph), None);
+    }
+
+    #[test]
+    fn test_greedy_colouring() {
+        // a crown graph: a_i <--> b_j for every i != j
+        let mut graph = Graph::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                if i != j {
+                    graph.add_undirected_edge(2 * i, 2 * j + 1);
+                }
+            }
+        }
+        // ascending order is the worst case here, each
+        // a_i / b_i pair needs a colour of its own
+        let greedy = greedy_colouring(&graph);
+        assert!(is_proper_colouring(&graph, &greedy));
+        assert_eq!(colour_count(&greedy), 4);
+
+        let dsatur = dsatur(&graph);
+        assert!(is_proper_colouring(&graph, &dsatur));
+        assert_eq!(colour_count(&dsatur), 2);
+        assert_eq!(chromatic_number(&graph), Some(2));
+    }
+
+    #[test]
+    fn test_dsatur() {
+        // wheel: a hub joined to every node of a 5-cycle
+        let mut graph = cycle_graph(5);
+        for node in 0..5 {
+            graph.add_undirected_edge(5, node);
+        }
+        let colouring = dsatur(&graph);
+        assert!(is_proper_colouring(&graph, &colouring));
+        assert_eq!(colour_count(&colouring), 4);
+        assert_eq!(colouring[&5], 0);
+    }
+
+    #[test]
+    fn test_k_colouring() {
+        // the Petersen graph needs 3 colours
+        let mut graph = Graph::new();
+        for i in 0..5 {
+            graph.add_undirected_edge(i, (i + 1) % 5);
+            graph.add_undirected_edge(i, i + 5);
+            graph.add_undirected_edge(i + 5, (i + 2) % 5 + 5);
+        }
+        assert_eq!(k_colouring(&graph, 2), None);
+        let colouring = k_colouring(&graph, 3).unwrap();
+        assert!(is_proper_colouring(&graph, &colouring));
+        assert_eq!(chromatic_number(&graph), Some(3));
+
+        assert_eq!(chromatic_number(&cycle_graph(6)), Some(2));
+        assert_eq!(chromatic_number(&cycle_graph(9)), Some(3));
+        assert_eq!(chromatic_number(&Graph::new()), Some(0));
+
+        let mut complete = Graph::new();
+        for u in 0..5 {
+            for v in u + 1..5 {
+                complete.add_undirected_edge(u, v);
+            }
+        }
+        assert_eq!(k_colouring(&complete, 4), None);
+        assert_eq!(chromatic_number(&complete), Some(5));
+    }
+
+    #[test]
+    fn test_not_proper() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_node(2);
+        let mut colouring = HashMap::from([(0, 0), (1, 1)]);
+        assert!(!is_proper_colouring(&graph, &colouring));
+        colouring.insert(2, 0);
+        assert!(is_proper_colouring(&graph, &colouring));
+        colouring.insert(1, 0);
+        assert!(!is_proper_colouring(&graph, &colouring));
+    }
+}
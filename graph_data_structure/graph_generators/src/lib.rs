@@ -0,0 +1,475 @@
+//! Deterministic graph generators.
+//!
+//! Every generator returns a `GeneratedGraph`: the nodes
+//! `0..node_count` and a list of edges. The same arguments,
+//! seed included, always give the same graph, which `build`
+//! then turns into the `Graph` of whichever crate is being
+//! tested. That way one random graph can be handed to
+//! several implementations and their answers compared.
+use std::collections::HashSet;
+
+pub type NodeId = usize;
+
+/// splitmix64, small and good enough to generate test graphs
+/// without pulling in a dependency. Any seed works, zero
+/// included.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// `true` with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        // the top 53 bits make a uniform float in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// What `GeneratedGraph::build` needs from a graph. A crate
+/// whose `Graph` gets fuzzed implements it, or its tests do.
+pub trait BuildGraph: Default {
+    fn add_node(&mut self, node_id: NodeId);
+
+    fn add_directed_edge(&mut self, u: NodeId, v: NodeId);
+
+    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_directed_edge(u, v);
+        self.add_directed_edge(v, u);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedGraph {
+    pub directed: bool,
+    pub node_count: usize,
+    /// Each undirected edge is listed once
+    pub edges: Vec<(NodeId, NodeId)>,
+}
+
+impl GeneratedGraph {
+    fn new(directed: bool, node_count: usize) -> Self {
+        Self { directed, node_count, edges: Vec::new() }
+    }
+
+    pub fn build<G: BuildGraph>(&self) -> G {
+        let mut graph = G::default();
+        for node in 0..self.node_count {
+            graph.add_node(node);
+        }
+        for (u, v) in &self.edges {
+            if self.directed {
+                graph.add_directed_edge(*u, *v);
+            } else {
+                graph.add_undirected_edge(*u, *v);
+            }
+        }
+        graph
+    }
+}
+
+/// G(n, p): every pair of distinct nodes gets an edge with
+/// probability `p`, each direction on its own if `directed`
+pub fn erdos_renyi(
+    node_count: usize,
+    p: f64,
+    directed: bool,
+    seed: u64,
+) -> GeneratedGraph {
+    let mut rng = Rng::new(seed);
+    let mut graph = GeneratedGraph::new(directed, node_count);
+    for u in 0..node_count {
+        let first = if directed { 0 } else { u + 1 };
+        for v in first..node_count {
+            if u != v && rng.chance(p) {
+                graph.edges.push((u, v));
+            }
+        }
+    }
+    graph
+}
+
+/// Preferential attachment: starting from a complete graph on
+/// `m + 1` nodes, every new node gets edges to `m` distinct
+/// older nodes, picked with a probability proportional to
+/// their degree. Gives the few very well connected hubs of a
+/// scale-free network. Undirected.
+pub fn barabasi_albert(
+    node_count: usize,
+    m: usize,
+    seed: u64,
+) -> GeneratedGraph {
+    assert!(m > 0, "every new node needs at least one edge");
+    let mut graph = complete(node_count.min(m + 1), false);
+    graph.node_count = node_count;
+    let mut rng = Rng::new(seed);
+    // every node shows up once for each of its edges, so a
+    // uniform pick from here is proportional to the degree
+    let mut ends: Vec<NodeId> =
+        graph.edges.iter().flat_map(|(u, v)| [*u, *v]).collect();
+    for node in m + 1..node_count {
+        let mut targets = Vec::with_capacity(m);
+        while targets.len() < m {
+            let target = ends[rng.below(ends.len())];
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        for target in targets {
+            graph.edges.push((target, node));
+            ends.extend([target, node]);
+        }
+    }
+    graph
+}
+
+/// Directed acyclic graph: the nodes are put in a random
+/// order and every pair gets an edge from the earlier to the
+/// later node with probability `p`. The node ids themselves
+/// are in no particular topological order.
+pub fn random_dag(node_count: usize, p: f64, seed: u64) -> GeneratedGraph {
+    let mut rng = Rng::new(seed);
+    let mut order: Vec<NodeId> = (0..node_count).collect();
+    rng.shuffle(&mut order);
+    let mut graph = GeneratedGraph::new(true, node_count);
+    for i in 0..node_count {
+        for j in i + 1..node_count {
+            if rng.chance(p) {
+                graph.edges.push((order[i], order[j]));
+            }
+        }
+    }
+    graph
+}
+
+/// Random tree: the nodes are put in a random order and each
+/// one is joined to a random node that came before it.
+/// Undirected, every tree on the nodes can come out but they
+/// aren't all equally likely.
+pub fn random_tree(node_count: usize, seed: u64) -> GeneratedGraph {
+    let mut rng = Rng::new(seed);
+    let mut order: Vec<NodeId> = (0..node_count).collect();
+    rng.shuffle(&mut order);
+    let mut graph = GeneratedGraph::new(false, node_count);
+    for i in 1..node_count {
+        graph.edges.push((order[rng.below(i)], order[i]));
+    }
+    graph
+}
+
+/// `rows` x `cols` grid, node `row * cols + col` joined to
+/// the nodes to its right and below it. Undirected.
+pub fn grid(rows: usize, cols: usize) -> GeneratedGraph {
+    let mut graph = GeneratedGraph::new(false, rows * cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            let node = row * cols + col;
+            if col + 1 < cols {
+                graph.edges.push((node, node + 1));
+            }
+            if row + 1 < rows {
+                graph.edges.push((node, node + cols));
+            }
+        }
+    }
+    graph
+}
+
+/// An edge between every pair of distinct nodes, both ways
+/// if `directed`
+pub fn complete(node_count: usize, directed: bool) -> GeneratedGraph {
+    let mut graph = GeneratedGraph::new(directed, node_count);
+    for u in 0..node_count {
+        for v in 0..node_count {
+            if u < v || (directed && u != v) {
+                graph.edges.push((u, v));
+            }
+        }
+    }
+    graph
+}
+
+/// Node 0 joined to every other node. Undirected.
+pub fn star(node_count: usize) -> GeneratedGraph {
+    let mut graph = GeneratedGraph::new(false, node_count);
+    graph.edges.extend((1..node_count).map(|node| (0, node)));
+    graph
+}
+
+/// 0 -- 1 -- ... -- n-1, pointing that way if `directed`
+pub fn path(node_count: usize, directed: bool) -> GeneratedGraph {
+    let mut graph = GeneratedGraph::new(directed, node_count);
+    graph.edges.extend((1..node_count).map(|node| (node - 1, node)));
+    graph
+}
+
+/// A path with its last node joined back to the first one.
+/// Needs at least three nodes when undirected, two when
+/// directed, to be a proper cycle.
+pub fn cycle(node_count: usize, directed: bool) -> GeneratedGraph {
+    let mut graph = path(node_count, directed);
+    if node_count > 1 {
+        graph.edges.push((node_count - 1, 0));
+    }
+    graph
+}
+
+/// Whether every edge shows up only once and none of them is
+/// a self loop
+pub fn is_simple(graph: &GeneratedGraph) -> bool {
+    let mut seen = HashSet::new();
+    graph.edges.iter().all(|(u, v)| {
+        let edge =
+            if graph.directed { (*u, *v) } else { (*u.min(v), *u.max(v)) };
+        u != v && seen.insert(edge)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use all_pairs_shortest_paths::floyd_warshall;
+    use compressed_graph::{connected_components_count, CsrGraph};
+
+    macro_rules! build_graph {
+        ($($graph:ty),*) => {$(
+            impl BuildGraph for $graph {
+                fn add_node(&mut self, node_id: NodeId) {
+                    <$graph>::add_node(self, node_id);
+                }
+
+                fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+                    <$graph>::add_directed_edge(self, u, v);
+                }
+            }
+        )*};
+    }
+
+    // the crates checked against each other below. These tests
+    // build a copy of this crate of their own, whose `BuildGraph`
+    // `compressed_graph` doesn't know about.
+    build_graph!(
+        all_pairs_shortest_paths::Graph,
+        compressed_graph::Graph,
+        has_cycle::Graph,
+        longest_path::Graph,
+        shortest_path::Graph,
+        topological_sort::Graph
+    );
+
+    fn degrees(graph: &GeneratedGraph) -> Vec<usize> {
+        let mut degrees = vec![0; graph.node_count];
+        for (u, v) in &graph.edges {
+            degrees[*u] += 1;
+            degrees[*v] += 1;
+        }
+        degrees
+    }
+
+    #[test]
+    fn test_shapes() {
+        assert_eq!(complete(5, false).edges.len(), 10);
+        assert_eq!(complete(5, true).edges.len(), 20);
+        assert_eq!(star(6).edges.len(), 5);
+        assert_eq!(degrees(&star(6))[0], 5);
+        assert_eq!(path(4, true).edges, vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(cycle(3, false).edges, vec![(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(path(0, false).edges, vec![]);
+        assert_eq!(cycle(1, false).edges, vec![]);
+        // 0 -- 1 -- 2
+        // |    |    |
+        // 3 -- 4 -- 5
+        assert_eq!(
+            grid(2, 3).edges,
+            vec![(0, 1), (0, 3), (1, 2), (1, 4), (2, 5), (3, 4), (4, 5)]
+        );
+        for graph in [complete(6, true), star(6), cycle(6, false), grid(3, 4)]
+        {
+            assert!(is_simple(&graph));
+        }
+    }
+
+    #[test]
+    fn test_seeded() {
+        for seed in 0..20 {
+            assert_eq!(
+                erdos_renyi(30, 0.2, false, seed),
+                erdos_renyi(30, 0.2, false, seed)
+            );
+            assert_ne!(
+                erdos_renyi(30, 0.2, true, seed),
+                erdos_renyi(30, 0.2, true, seed + 1)
+            );
+            assert_eq!(random_dag(30, 0.2, seed), random_dag(30, 0.2, seed));
+            assert_eq!(random_tree(30, seed), random_tree(30, seed));
+            assert!(is_simple(&erdos_renyi(30, 0.5, true, seed)));
+            assert!(is_simple(&random_dag(30, 0.5, seed)));
+        }
+        assert_eq!(erdos_renyi(10, 0.0, true, 1).edges, vec![]);
+        assert_eq!(erdos_renyi(10, 1.0, true, 1), complete(10, true));
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        for seed in 0..20 {
+            let graph = barabasi_albert(100, 3, seed);
+            assert!(is_simple(&graph));
+            // 6 edges between the first 4 nodes, then 3 per node
+            assert_eq!(graph.edges.len(), 6 + 96 * 3);
+            let degrees = degrees(&graph);
+            assert!(degrees.iter().all(|degree| *degree >= 3));
+            // the early nodes should have become hubs
+            assert!(degrees.iter().max().unwrap() > &15);
+        }
+        assert_eq!(barabasi_albert(2, 3, 0), complete(2, false));
+    }
+
+    #[test]
+    fn test_trees() {
+        for seed in 0..100 {
+            let generated = random_tree(25, seed);
+            assert_eq!(generated.edges.len(), 24);
+            let graph: compressed_graph::Graph = generated.build();
+            assert_eq!(connected_components_count(&graph), 1);
+            let graph: has_cycle::Graph = generated.build();
+            let adjacency_list = graph.adjacency_list();
+            assert_eq!(has_cycle::find_undirected_cycle(adjacency_list), None);
+        }
+        let graph: has_cycle::Graph = grid(2, 2).build();
+        assert!(
+            has_cycle::find_undirected_cycle(graph.adjacency_list()).is_some()
+        );
+    }
+
+    /// `has_cycle`, `topological_sort` and `graph_traversal`
+    /// should agree on whether a random directed graph has a
+    /// cycle, and a random DAG never should
+    #[test]
+    fn fuzz_cycle_detection() {
+        let (mut cyclic, mut acyclic) = (0, 0);
+        for seed in 0..300 {
+            let p = [0.05, 0.1, 0.2][seed as usize % 3];
+            let generated = erdos_renyi(10, p, true, seed);
+            let expected = has_cycle::has_cycle(generated.build());
+            let graph: topological_sort::Graph = generated.build();
+            assert_eq!(topological_sort::kahn(&graph).is_err(), expected);
+            assert_eq!(
+                topological_sort::topological_sort_dfs(&graph).is_err(),
+                expected
+            );
+            let graph = CsrGraph::from(&generated.build());
+            let cycle = graph_traversal::find_cycle(&graph);
+            assert_eq!(cycle.is_some(), expected);
+            if let Some(cycle) = cycle {
+                for (i, node) in cycle.iter().enumerate() {
+                    let next = cycle[(i + 1) % cycle.len()];
+                    assert!(generated.edges.contains(&(*node, next)));
+                }
+                cyclic += 1;
+            } else {
+                acyclic += 1;
+            }
+
+            let dag = random_dag(10, p * 3.0, seed);
+            assert!(!has_cycle::has_cycle(dag.build()));
+            assert!(topological_sort::kahn(&dag.build()).is_ok());
+        }
+        // make sure both answers actually came up
+        assert!(cyclic > 0 && acyclic > 0);
+    }
+
+    /// Breadth-first searches of `shortest_path` and
+    /// `compressed_graph` checked against Floyd-Warshall
+    #[test]
+    fn fuzz_shortest_path() {
+        for seed in 0..30 {
+            let graphs = [
+                erdos_renyi(15, 0.15, false, seed),
+                erdos_renyi(15, 0.1, true, seed),
+                barabasi_albert(15, 2, seed),
+                random_tree(15, seed),
+                grid(3, 5),
+            ];
+            for generated in graphs {
+                let graph: shortest_path::Graph = generated.build();
+                let csr = CsrGraph::from(&generated.build());
+                let distances = floyd_warshall(&generated.build()).unwrap();
+                for start in 0..generated.node_count {
+                    for dest in 0..generated.node_count {
+                        let expected = distances
+                            .distance(start, dest)
+                            .map(|distance| distance as usize);
+                        assert_eq!(
+                            shortest_path::shortest_path(&graph, start, dest),
+                            expected
+                        );
+                        assert_eq!(
+                            shortest_path::bidirectional_shortest_path(
+                                &graph, start, dest
+                            )
+                            .map(|path| path.len() - 1),
+                            expected
+                        );
+                        assert_eq!(
+                            compressed_graph::shortest_path(&csr, start, dest),
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// `longest_path` checked against Floyd-Warshall with every
+    /// edge weighing -1: in a DAG the shortest of those paths
+    /// is the longest path
+    #[test]
+    fn fuzz_longest_path() {
+        for seed in 0..100 {
+            let generated = random_dag(12, 0.3, seed);
+            let mut graph = all_pairs_shortest_paths::Graph::new();
+            for node in 0..generated.node_count {
+                graph.add_node(node);
+            }
+            for (u, v) in &generated.edges {
+                graph.add_weighted_directed_edge(*u, *v, -1);
+            }
+            let distances = floyd_warshall(&graph).unwrap();
+            let mut expected = 0;
+            for u in 0..generated.node_count {
+                for v in 0..generated.node_count {
+                    let distance = distances.distance(u, v).unwrap_or(0);
+                    expected = expected.max(-distance);
+                }
+            }
+            let longest = longest_path::longest_path(generated.build());
+            assert_eq!(longest.map(i64::from), Ok(expected));
+        }
+        let longest = longest_path::longest_path(path(20, true).build());
+        assert_eq!(longest, Ok(19));
+        let longest = longest_path::longest_path(cycle(5, true).build());
+        assert!(longest.is_err());
+    }
+}
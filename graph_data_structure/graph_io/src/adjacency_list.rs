@@ -0,0 +1,113 @@
+use crate::{parse_node, parse_weight, strip_comment, Graph, ParseError};
+
+/// Reads one node per line followed by a colon and the nodes
+/// its edges lead to, each with an optional `:weight`:
+///
+/// ```text
+/// 0: 1 2:7
+/// 1: 2:-4
+/// 5:
+/// ```
+///
+/// In an undirected graph every edge is only listed under
+/// one of its ends. Blank lines and everything after a `#`
+/// are skipped.
+pub fn read_adjacency_list(
+    input: &str,
+    directed: bool,
+) -> Result<Graph, ParseError> {
+    let mut graph = Graph::new(directed);
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((node, neighbours)) = line.split_once(':') else {
+            return Err(ParseError::new(
+                line_number,
+                format!("expected `node: neighbours`, found `{}`", line),
+            ));
+        };
+        let node = parse_node(node.trim(), line_number)?;
+        graph.add_node(node);
+
+        for neighbour in neighbours.split_whitespace() {
+            match neighbour.split_once(':') {
+                Some((neighbour, weight)) => graph.add_weighted_edge(
+                    node,
+                    parse_node(neighbour, line_number)?,
+                    parse_weight(weight, line_number)?,
+                ),
+                None => {
+                    graph.add_edge(node, parse_node(neighbour, line_number)?)
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Writes every node on a line of its own, followed by the
+/// edges starting from it. Labels have no place in this
+/// format and are left out.
+pub fn write_adjacency_list(graph: &Graph) -> String {
+    let mut output = String::new();
+    for node in graph.nodes() {
+        output.push_str(&format!("{}:", node));
+        for edge in graph.edges().iter().filter(|edge| edge.from == node) {
+            match edge.weight {
+                Some(weight) => {
+                    output.push_str(&format!(" {}:{}", edge.to, weight))
+                }
+                None => output.push_str(&format!(" {}", edge.to)),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_graph;
+
+    #[test]
+    fn test_read_adjacency_list() {
+        let input = "0: 1 2:7\n# the rest\n1: 2:-4\n2 : 2\n\n5:\n";
+        let graph = read_adjacency_list(input, true).unwrap();
+        let expected = example_graph();
+        assert_eq!(graph.nodes(), expected.nodes());
+        assert_eq!(graph.edges().len(), 4);
+        for edge in expected.edges() {
+            assert!(graph.edges().contains(edge));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph = example_graph();
+        let output = write_adjacency_list(&graph);
+        assert_eq!(output, "0: 1 2:7\n1: 2:-4\n2: 2\n5:\n");
+        let read = read_adjacency_list(&output, true).unwrap();
+        assert_eq!(read.nodes(), graph.nodes());
+        assert_eq!(write_adjacency_list(&read), output);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = read_adjacency_list("0: 1\n1 2\n", true).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(2, "expected `node: neighbours`, found `1 2`")
+        );
+
+        let error = read_adjacency_list("0: 1\n\n1: 2:x", true).unwrap_err();
+        assert_eq!(error.to_string(), "line 3: expected a weight, found `x`");
+
+        let error = read_adjacency_list("a: 1", true).unwrap_err();
+        assert_eq!(error, ParseError::new(1, "expected a node id, found `a`"));
+    }
+}
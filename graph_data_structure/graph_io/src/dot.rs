@@ -0,0 +1,480 @@
+use crate::{parse_weight, Graph, NodeId, ParseError};
+
+/// Reads the part of the Graphviz DOT language that describes
+/// a plain graph:
+///
+/// ```text
+/// digraph routes {
+///     0 [label="warehouse"];
+///     0 -> 1 -> 2 [weight=3];
+///     5
+/// }
+/// ```
+///
+/// `graph` with `--` edges is undirected, `digraph` with `->`
+/// edges is directed. Node ids have to be non-negative
+/// integers, quoted or not. A node's `label` and an edge's
+/// `weight` are kept, every other attribute (and attribute
+/// defaults like `node [shape=box]`) is skipped. Subgraphs
+/// aren't supported.
+pub fn read_dot(input: &str) -> Result<Graph, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, position: 0, last_line: 1 };
+    parser.graph()
+}
+
+/// Writes the graph as DOT, nodes first with their labels,
+/// then the edges with their weights
+pub fn write_dot(graph: &Graph) -> String {
+    let (keyword, edge_op) =
+        if graph.is_directed() { ("digraph", "->") } else { ("graph", "--") };
+    let mut output = format!("{} {{\n", keyword);
+    for node in graph.nodes() {
+        match graph.label(node) {
+            Some(label) => output.push_str(&format!(
+                "    {} [label=\"{}\"];\n",
+                node,
+                escape(label)
+            )),
+            None => output.push_str(&format!("    {};\n", node)),
+        }
+    }
+    for edge in graph.edges() {
+        output.push_str(&format!("    {} {} {}", edge.from, edge_op, edge.to));
+        if let Some(weight) = edge.weight {
+            output.push_str(&format!(" [weight={}]", weight));
+        }
+        output.push_str(";\n");
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    // a name or a number
+    Id(String),
+    // a quoted string, quotes removed
+    Quoted(String),
+    // one of `{ } [ ] = ; , -> --`
+    Symbol(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                start,
+                                "comment is never closed",
+                            ))
+                        }
+                    }
+                }
+            }
+            '{' => tokens.push((Token::Symbol("{"), line)),
+            '}' => tokens.push((Token::Symbol("}"), line)),
+            '[' => tokens.push((Token::Symbol("["), line)),
+            ']' => tokens.push((Token::Symbol("]"), line)),
+            '=' => tokens.push((Token::Symbol("="), line)),
+            ';' => tokens.push((Token::Symbol(";"), line)),
+            ',' => tokens.push((Token::Symbol(","), line)),
+            '-' if chars.next_if_eq(&'>').is_some() => {
+                tokens.push((Token::Symbol("->"), line))
+            }
+            '-' if chars.next_if_eq(&'-').is_some() => {
+                tokens.push((Token::Symbol("--"), line))
+            }
+            '"' => {
+                let start = line;
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') if chars.peek() == Some(&'"') => {
+                            text.push(chars.next().unwrap())
+                        }
+                        Some('\\') if chars.peek() == Some(&'\\') => {
+                            text.push(chars.next().unwrap())
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                start,
+                                "string is never closed",
+                            ))
+                        }
+                    }
+                }
+                tokens.push((Token::Quoted(text), start));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' => {
+                let mut text = c.to_string();
+                while let Some(c) = chars
+                    .next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    text.push(c);
+                }
+                tokens.push((Token::Id(text), line));
+            }
+            c => {
+                return Err(ParseError::new(
+                    line,
+                    format!("unexpected character `{}`", c),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    // line of the last token, for errors at the very end
+    last_line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.last_line, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (token, line) = self.tokens.get(self.position)?.clone();
+        self.position += 1;
+        self.last_line = line;
+        Some(token)
+    }
+
+    fn peek_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(next)) if *next == symbol)
+    }
+
+    fn next_if_symbol(&mut self, symbol: &str) -> bool {
+        if self.peek_symbol(symbol) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseError> {
+        if self.next_if_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        let found = match self.peek() {
+            Some(Token::Id(text)) => format!("`{}`", text),
+            Some(Token::Quoted(text)) => format!("\"{}\"", text),
+            Some(Token::Symbol(symbol)) => format!("`{}`", symbol),
+            None => "the end of the input".to_string(),
+        };
+        ParseError::new(
+            self.line(),
+            format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn graph(&mut self) -> Result<Graph, ParseError> {
+        if self.peek() == Some(&Token::Id("strict".to_string())) {
+            self.next();
+        }
+        let directed = match self.peek() {
+            Some(Token::Id(keyword)) if keyword == "digraph" => true,
+            Some(Token::Id(keyword)) if keyword == "graph" => false,
+            _ => return Err(self.unexpected("`graph` or `digraph`")),
+        };
+        self.next();
+        if let Some(Token::Id(_) | Token::Quoted(_)) = self.peek() {
+            // the name of the graph
+            self.next();
+        }
+        self.expect_symbol("{")?;
+
+        let mut graph = Graph::new(directed);
+        while !self.next_if_symbol("}") {
+            self.statement(&mut graph)?;
+            self.next_if_symbol(";");
+        }
+
+        if self.peek().is_some() {
+            return Err(self.unexpected("the end of the input"));
+        }
+        Ok(graph)
+    }
+
+    fn statement(&mut self, graph: &mut Graph) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(Token::Id(keyword))
+                if ["graph", "node", "edge"].contains(&keyword.as_str()) =>
+            {
+                // attribute defaults
+                self.next();
+                self.attributes()?;
+                return Ok(());
+            }
+            Some(Token::Id(keyword)) if keyword == "subgraph" => {
+                return Err(ParseError::new(
+                    self.line(),
+                    "subgraphs are not supported",
+                ));
+            }
+            Some(Token::Symbol("{")) => {
+                return Err(ParseError::new(
+                    self.line(),
+                    "subgraphs are not supported",
+                ));
+            }
+            _ => {}
+        }
+
+        let second = self.tokens.get(self.position + 1);
+        if matches!(second, Some((Token::Symbol("="), _))) {
+            // graph attribute like `rankdir = LR`
+            self.next();
+            self.next();
+            return self.attribute_value().map(|_| ());
+        }
+
+        let line = self.line();
+        let first = self.node_id()?;
+        let edge_op = if graph.is_directed() { "->" } else { "--" };
+        let wrong_op = if graph.is_directed() { "--" } else { "->" };
+        let mut chain = vec![first];
+        loop {
+            if self.next_if_symbol(edge_op) {
+                chain.push(self.node_id()?);
+            } else if self.peek_symbol(wrong_op) {
+                let kind =
+                    if graph.is_directed() { "digraph" } else { "graph" };
+                return Err(ParseError::new(
+                    self.line(),
+                    format!(
+                        "`{}` edges can't be used in a {}",
+                        wrong_op, kind
+                    ),
+                ));
+            } else {
+                break;
+            }
+        }
+
+        let attributes = self.attributes()?;
+        if chain.len() == 1 {
+            graph.add_node(first);
+            if let Some((_, label)) =
+                attributes.iter().find(|(key, _)| key == "label")
+            {
+                graph.set_label(first, label);
+            }
+            return Ok(());
+        }
+
+        let weight = match attributes.iter().find(|(key, _)| key == "weight") {
+            Some((_, weight)) => Some(parse_weight(weight, line)?),
+            None => None,
+        };
+        for edge in chain.windows(2) {
+            match weight {
+                Some(weight) => {
+                    graph.add_weighted_edge(edge[0], edge[1], weight)
+                }
+                None => graph.add_edge(edge[0], edge[1]),
+            }
+        }
+        Ok(())
+    }
+
+    fn node_id(&mut self) -> Result<NodeId, ParseError> {
+        let line = self.line();
+        match self.peek() {
+            Some(Token::Id(text) | Token::Quoted(text)) => {
+                let node = text.parse().map_err(|_| {
+                    ParseError::new(
+                        line,
+                        format!(
+                            "node ids have to be non-negative integers, found `{}`",
+                            text
+                        ),
+                    )
+                })?;
+                self.next();
+                Ok(node)
+            }
+            _ => Err(self.unexpected("a node id")),
+        }
+    }
+
+    fn attribute_value(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Id(text) | Token::Quoted(text)) => {
+                let text = text.clone();
+                self.next();
+                Ok(text)
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// Any number of `[key=value, ...]` lists
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ParseError> {
+        let mut attributes = Vec::new();
+        while self.next_if_symbol("[") {
+            while !self.next_if_symbol("]") {
+                let key = match self.peek() {
+                    Some(Token::Id(key) | Token::Quoted(key)) => key.clone(),
+                    _ => return Err(self.unexpected("an attribute name")),
+                };
+                self.next();
+                self.expect_symbol("=")?;
+                attributes.push((key, self.attribute_value()?));
+                if !self.next_if_symbol(",") {
+                    self.next_if_symbol(";");
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_graph;
+
+    #[test]
+    fn test_read_dot() {
+        let input = r#"
+            // delivery routes
+            strict digraph routes {
+                rankdir = LR;
+                node [shape=box, color="grey"]
+                1 [label="warehouse \"B\""]
+                "5" [label=depot; shape=circle];
+                0 -> 1
+                1 -> 2 [weight=-4] 0 -> 2 [weight="7", color=red];
+                /* a
+                   self-loop */
+                2 -> 2;
+            }
+        "#;
+        let graph = read_dot(input).unwrap();
+        let expected = example_graph();
+        assert_eq!(graph.edges(), expected.edges());
+        assert_eq!(graph.nodes(), expected.nodes());
+        assert_eq!(graph.label(1), Some("warehouse \"B\""));
+        assert_eq!(graph.label(5), Some("depot"));
+        assert!(graph.is_directed());
+    }
+
+    #[test]
+    fn test_edge_chain() {
+        let graph = read_dot("graph { 0 -- 1 -- 2 [weight=3]; 4 }").unwrap();
+        assert!(!graph.is_directed());
+        assert_eq!(graph.nodes(), vec![0, 1, 2, 4]);
+        assert_eq!(graph.edges().len(), 2);
+        assert!(graph.edges().iter().all(|edge| edge.weight == Some(3)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph = example_graph();
+        let output = write_dot(&graph);
+        assert_eq!(
+            output,
+            "digraph {\n    0;\n    1 [label=\"warehouse \\\"B\\\"\"];\n    2;\n    5 [label=\"depot\"];\n    0 -> 1;\n    1 -> 2 [weight=-4];\n    0 -> 2 [weight=7];\n    2 -> 2;\n}\n"
+        );
+        assert_eq!(read_dot(&output), Ok(graph));
+
+        let mut graph = Graph::new(false);
+        graph.add_edge(3, 4);
+        assert_eq!(read_dot(&write_dot(&graph)), Ok(graph));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error =
+            read_dot("digraph {\n  0 -> 1;\n  1 -- 2;\n}").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(3, "`--` edges can't be used in a digraph")
+        );
+
+        let error = read_dot("graph {\n  a -- b\n}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: node ids have to be non-negative integers, found `a`"
+        );
+
+        let error =
+            read_dot("graph {\n 0 -- 1 [weight=heavy]\n}").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(2, "expected a weight, found `heavy`")
+        );
+
+        let error = read_dot("graph {\n 0 [label=\"oops]\n}").unwrap_err();
+        assert_eq!(error, ParseError::new(2, "string is never closed"));
+
+        let error = read_dot("graph {\n 0 -- 1").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(
+                2,
+                "expected a node id, found the end of the input"
+            )
+        );
+
+        let error = read_dot("tree { }").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(1, "expected `graph` or `digraph`, found `tree`")
+        );
+
+        let error = read_dot("graph {\n\n subgraph { 0 } }").unwrap_err();
+        assert_eq!(error, ParseError::new(3, "subgraphs are not supported"));
+    }
+}
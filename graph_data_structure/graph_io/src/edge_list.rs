@@ -0,0 +1,103 @@
+use crate::{parse_node, parse_weight, strip_comment, Graph, ParseError};
+
+/// Reads one edge per line as `u v` or `u v weight`. A line
+/// with a single node adds that node on its own. Blank lines
+/// and everything after a `#` are skipped.
+pub fn read_edge_list(
+    input: &str,
+    directed: bool,
+) -> Result<Graph, ParseError> {
+    let mut graph = Graph::new(directed);
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let tokens: Vec<&str> =
+            strip_comment(line).split_whitespace().collect();
+        match tokens[..] {
+            [] => {}
+            [u] => graph.add_node(parse_node(u, line_number)?),
+            [u, v] => graph.add_edge(
+                parse_node(u, line_number)?,
+                parse_node(v, line_number)?,
+            ),
+            [u, v, weight] => graph.add_weighted_edge(
+                parse_node(u, line_number)?,
+                parse_node(v, line_number)?,
+                parse_weight(weight, line_number)?,
+            ),
+            _ => {
+                return Err(ParseError::new(
+                    line_number,
+                    format!(
+                        "expected `u v [w]`, found {} values",
+                        tokens.len()
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Writes the nodes without any edges first, one per line,
+/// then the edges. Labels have no place in this format and
+/// are left out.
+pub fn write_edge_list(graph: &Graph) -> String {
+    let mut output = String::new();
+    for node in graph.isolated_nodes() {
+        output.push_str(&format!("{}\n", node));
+    }
+    for edge in graph.edges() {
+        match edge.weight {
+            Some(weight) => output
+                .push_str(&format!("{} {} {}\n", edge.from, edge.to, weight)),
+            None => output.push_str(&format!("{} {}\n", edge.from, edge.to)),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_graph;
+
+    #[test]
+    fn test_read_edge_list() {
+        let input =
+            "# routes\n0 1\n\n1 2 -4   # the short one\n 0 2 7\n2 2\n5\n";
+        let graph = read_edge_list(input, true).unwrap();
+        let expected = example_graph();
+        assert_eq!(graph.edges(), expected.edges());
+        assert_eq!(graph.nodes(), expected.nodes());
+        assert!(graph.is_directed());
+        assert_eq!(graph.label(1), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph = example_graph();
+        let output = write_edge_list(&graph);
+        assert_eq!(output, "5\n0 1\n1 2 -4\n0 2 7\n2 2\n");
+        let read = read_edge_list(&output, true).unwrap();
+        assert_eq!(read.edges(), graph.edges());
+        assert_eq!(read.nodes(), graph.nodes());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = read_edge_list("0 1\n1 x\n", false).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected a node id, found `x`");
+
+        let error = read_edge_list("0 1 2.5", false).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(1, "expected a weight, found `2.5`")
+        );
+
+        let error = read_edge_list("\n\n0 1 2 3\n", false).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(3, "expected `u v [w]`, found 4 values")
+        );
+    }
+}
@@ -0,0 +1,557 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::{Graph, NodeId, ParseError, Weight};
+
+/// Reads a graph stored as JSON:
+///
+/// ```text
+/// {
+///   "directed": true,
+///   "nodes": [{"id": 0, "label": "warehouse"}, {"id": 5}],
+///   "edges": [{"from": 0, "to": 1, "weight": 3}, {"from": 1, "to": 2}]
+/// }
+/// ```
+///
+/// `directed` defaults to `true`, `nodes` and `edges` to
+/// empty and both `label` and `weight` are optional. Nodes
+/// only used by edges don't have to be listed. Any other
+/// fields are skipped.
+pub fn read_json(input: &str) -> Result<Graph, ParseError> {
+    let mut parser = Parser { chars: input.chars().peekable(), line: 1 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err(parser.error("expected the end of the input"));
+    }
+
+    let fields = value.as_object("the graph")?;
+    let directed = match field(fields, "directed") {
+        Some(directed) => directed.as_bool("`directed`")?,
+        None => true,
+    };
+    let mut graph = Graph::new(directed);
+
+    if let Some(nodes) = field(fields, "nodes") {
+        for node in nodes.as_array("`nodes`")? {
+            let fields = node.as_object("a node")?;
+            let id = required(node, fields, "id")?.as_node("`id`")?;
+            graph.add_node(id);
+            if let Some(label) = field(fields, "label") {
+                graph.set_label(id, label.as_str("`label`")?);
+            }
+        }
+    }
+
+    if let Some(edges) = field(fields, "edges") {
+        for edge in edges.as_array("`edges`")? {
+            let fields = edge.as_object("an edge")?;
+            let from = required(edge, fields, "from")?.as_node("`from`")?;
+            let to = required(edge, fields, "to")?.as_node("`to`")?;
+            match field(fields, "weight") {
+                Some(weight) => {
+                    graph.add_weighted_edge(from, to, weight.as_weight()?)
+                }
+                None => graph.add_edge(from, to),
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Writes the graph as JSON in the shape `read_json` reads,
+/// one node or edge per line
+pub fn write_json(graph: &Graph) -> String {
+    let nodes: Vec<String> = graph
+        .nodes()
+        .into_iter()
+        .map(|node| match graph.label(node) {
+            Some(label) => {
+                format!(
+                    "{{\"id\": {}, \"label\": \"{}\"}}",
+                    node,
+                    escape(label)
+                )
+            }
+            None => format!("{{\"id\": {}}}", node),
+        })
+        .collect();
+    let edges: Vec<String> = graph
+        .edges()
+        .iter()
+        .map(|edge| match edge.weight {
+            Some(weight) => format!(
+                "{{\"from\": {}, \"to\": {}, \"weight\": {}}}",
+                edge.from, edge.to, weight
+            ),
+            None => {
+                format!("{{\"from\": {}, \"to\": {}}}", edge.from, edge.to)
+            }
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"directed\": {},\n  \"nodes\": {},\n  \"edges\": {}\n}}\n",
+        graph.is_directed(),
+        array(&nodes),
+        array(&edges)
+    )
+}
+
+fn array(items: &[String]) -> String {
+    if items.is_empty() {
+        return "[]".to_string();
+    }
+    format!("[\n    {}\n  ]", items.join(",\n    "))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A JSON value and the line it starts on
+#[derive(Clone, Debug, PartialEq)]
+struct Value {
+    line: usize,
+    kind: Kind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Null,
+    Bool(bool),
+    // whole numbers are all we need, anything else is kept
+    // as written so we can complain about it
+    Integer(i64),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+fn field<'a>(fields: &'a [(String, Value)], name: &str) -> Option<&'a Value> {
+    fields.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+}
+
+fn required<'a>(
+    object: &Value,
+    fields: &'a [(String, Value)],
+    name: &str,
+) -> Result<&'a Value, ParseError> {
+    field(fields, name).ok_or_else(|| {
+        ParseError::new(object.line, format!("missing field `{}`", name))
+    })
+}
+
+impl Value {
+    fn describe(&self) -> String {
+        match &self.kind {
+            Kind::Null => "null".to_string(),
+            Kind::Bool(value) => value.to_string(),
+            Kind::Integer(value) => value.to_string(),
+            Kind::Number(value) => value.clone(),
+            Kind::String(value) => format!("\"{}\"", value),
+            Kind::Array(_) => "an array".to_string(),
+            Kind::Object(_) => "an object".to_string(),
+        }
+    }
+
+    fn expected(&self, what: &str, name: &str) -> ParseError {
+        ParseError::new(
+            self.line,
+            format!(
+                "expected {} to be {}, found {}",
+                name,
+                what,
+                self.describe()
+            ),
+        )
+    }
+
+    fn as_object(&self, name: &str) -> Result<&[(String, Value)], ParseError> {
+        match &self.kind {
+            Kind::Object(fields) => Ok(fields),
+            _ => Err(self.expected("an object", name)),
+        }
+    }
+
+    fn as_array(&self, name: &str) -> Result<&[Value], ParseError> {
+        match &self.kind {
+            Kind::Array(items) => Ok(items),
+            _ => Err(self.expected("an array", name)),
+        }
+    }
+
+    fn as_bool(&self, name: &str) -> Result<bool, ParseError> {
+        match self.kind {
+            Kind::Bool(value) => Ok(value),
+            _ => Err(self.expected("true or false", name)),
+        }
+    }
+
+    fn as_str(&self, name: &str) -> Result<&str, ParseError> {
+        match &self.kind {
+            Kind::String(value) => Ok(value),
+            _ => Err(self.expected("a string", name)),
+        }
+    }
+
+    fn as_node(&self, name: &str) -> Result<NodeId, ParseError> {
+        match self.kind {
+            Kind::Integer(value) if value >= 0 => Ok(value as NodeId),
+            _ => Err(self.expected("a node id", name)),
+        }
+    }
+
+    fn as_weight(&self) -> Result<Weight, ParseError> {
+        match self.kind {
+            Kind::Integer(value) => Ok(value),
+            _ => Err(self.expected("a whole number", "`weight`")),
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, message)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.next_if(|c| c.is_whitespace()) {
+            if c == '\n' {
+                self.line += 1;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => {
+                Err(self
+                    .error(format!("expected `{}`, found `{}`", expected, c)))
+            }
+            None => Err(self.error(format!(
+                "expected `{}`, found the end of the input",
+                expected
+            ))),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        let line = self.line;
+        let kind = match self.chars.peek().copied() {
+            Some('{') => self.object()?,
+            Some('[') => self.array()?,
+            Some('"') => Kind::String(self.string()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) if c.is_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphabetic()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "null" => Kind::Null,
+                    "true" => Kind::Bool(true),
+                    "false" => Kind::Bool(false),
+                    _ => {
+                        return Err(
+                            self.error(format!("unexpected word `{}`", word))
+                        )
+                    }
+                }
+            }
+            Some(c) => {
+                return Err(self.error(format!("unexpected character `{}`", c)))
+            }
+            None => {
+                return Err(
+                    self.error("expected a value, found the end of the input")
+                )
+            }
+        };
+        Ok(Value { line, kind })
+    }
+
+    fn object(&mut self) -> Result<Kind, ParseError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Kind::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error("expected a field name in quotes"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Kind::Object(fields)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Kind, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Kind::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Kind::Array(items)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        let start = self.line;
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape()?),
+                Some('\n') => {
+                    return Err(ParseError::new(
+                        start,
+                        "string is never closed",
+                    ))
+                }
+                Some(c) => text.push(c),
+                None => {
+                    return Err(ParseError::new(
+                        start,
+                        "string is never closed",
+                    ))
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let c = match self.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex()?;
+                if !(0xd800..0xdc00).contains(&high) {
+                    return char::from_u32(high)
+                        .ok_or_else(|| self.error("invalid `\\u` escape"));
+                }
+                // the first half of a surrogate pair, the
+                // second half has to follow
+                if self.next() != Some('\\') || self.next() != Some('u') {
+                    return Err(self.error("invalid `\\u` escape"));
+                }
+                let low = self.hex()?;
+                if !(0xdc00..0xe000).contains(&low) {
+                    return Err(self.error("invalid `\\u` escape"));
+                }
+                let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                return char::from_u32(code)
+                    .ok_or_else(|| self.error("invalid `\\u` escape"));
+            }
+            _ => return Err(self.error("invalid escape in string")),
+        };
+        Ok(c)
+    }
+
+    fn hex(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("invalid `\\u` escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Kind, ParseError> {
+        let mut text = String::new();
+        while let Some(c) = self.chars.next_if(|c| {
+            c.is_ascii_digit() || ['-', '+', '.', 'e', 'E'].contains(c)
+        }) {
+            text.push(c);
+        }
+        if let Ok(value) = text.parse() {
+            return Ok(Kind::Integer(value));
+        }
+        if text.parse::<f64>().is_ok() {
+            return Ok(Kind::Number(text));
+        }
+        Err(self.error(format!("invalid number `{}`", text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_graph;
+
+    #[test]
+    fn test_read_json() {
+        let input = r#"
+            {
+                "name": "routes",
+                "nodes": [
+                    {"id": 1, "label": "warehouse \"B\""},
+                    {"id": 5, "label": "dépôt", "x": [1.5, null]}
+                ],
+                "edges": [
+                    {"from": 0, "to": 1},
+                    {"from": 1, "to": 2, "weight": -4},
+                    {"to": 2, "from": 0, "weight": 7},
+                    {"from": 2, "to": 2}
+                ]
+            }
+        "#;
+        let graph = read_json(input).unwrap();
+        let expected = example_graph();
+        assert!(graph.is_directed());
+        assert_eq!(graph.edges(), expected.edges());
+        assert_eq!(graph.nodes(), expected.nodes());
+        assert_eq!(graph.label(1), Some("warehouse \"B\""));
+        assert_eq!(graph.label(5), Some("dépôt"));
+
+        let graph = read_json(r#"{"directed": false}"#).unwrap();
+        assert_eq!(graph, Graph::new(false));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = example_graph();
+        graph.set_label(2, "tab\there \u{1F69A}");
+        let output = write_json(&graph);
+        assert_eq!(
+            output,
+            r#"{
+  "directed": true,
+  "nodes": [
+    {"id": 0},
+    {"id": 1, "label": "warehouse \"B\""},
+    {"id": 2, "label": "tab\there 🚚"},
+    {"id": 5, "label": "depot"}
+  ],
+  "edges": [
+    {"from": 0, "to": 1},
+    {"from": 1, "to": 2, "weight": -4},
+    {"from": 0, "to": 2, "weight": 7},
+    {"from": 2, "to": 2}
+  ]
+}
+"#
+        );
+        assert_eq!(read_json(&output), Ok(graph));
+        let error = read_json("\"\\ud83d\\ude9a\"").unwrap_err();
+        assert_eq!(
+            error.message,
+            "expected the graph to be an object, found \"🚚\""
+        );
+
+        let graph = Graph::new(false);
+        assert_eq!(
+            write_json(&graph),
+            "{\n  \"directed\": false,\n  \"nodes\": [],\n  \"edges\": []\n}\n"
+        );
+        assert_eq!(read_json(&write_json(&graph)), Ok(graph));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = read_json("{\n  \"edges\": [\n    {\"from\": 0}\n  ]\n}")
+            .unwrap_err();
+        assert_eq!(error, ParseError::new(3, "missing field `to`"));
+
+        let error = read_json(
+            "{\"edges\": [\n{\"from\": 0, \"to\": 1, \"weight\": 2.5}]}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: expected `weight` to be a whole number, found 2.5"
+        );
+
+        let error = read_json("{\"nodes\": [{\"id\": -1}]}").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(1, "expected `id` to be a node id, found -1")
+        );
+
+        // a surrogate pair's second half has to be a low surrogate
+        let error = read_json("\"\\ud83d\\u0041\"").unwrap_err();
+        assert_eq!(error, ParseError::new(1, "invalid `\\u` escape"));
+
+        let error = read_json("{\n\"nodes\": [1 2]}").unwrap_err();
+        assert_eq!(error, ParseError::new(2, "expected `,` or `]`"));
+
+        let error = read_json("{\"directed\": \"yes\"}").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(
+                1,
+                "expected `directed` to be true or false, found \"yes\""
+            )
+        );
+
+        let error = read_json("[]\n[]").unwrap_err();
+        assert_eq!(error, ParseError::new(2, "expected the end of the input"));
+
+        let error = read_json("\n\n{\"nodes\": [\"oops]}").unwrap_err();
+        assert_eq!(error, ParseError::new(3, "string is never closed"));
+
+        let error = read_json("{\"nodes\": [{\"id\": 1}, ").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(1, "expected a value, found the end of the input")
+        );
+    }
+}
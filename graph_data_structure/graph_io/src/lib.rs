@@ -0,0 +1,200 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+};
+
+mod adjacency_list;
+mod dot;
+mod edge_list;
+mod json;
+
+pub use adjacency_list::{read_adjacency_list, write_adjacency_list};
+pub use dot::{read_dot, write_dot};
+pub use edge_list::{read_edge_list, write_edge_list};
+pub use json::{read_json, write_json};
+
+pub type NodeId = usize;
+pub type Weight = i64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub weight: Option<Weight>,
+}
+
+/// A graph as it's stored in a file: nodes with an optional
+/// label and edges with an optional weight, in the order they
+/// were read
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    directed: bool,
+    labels: BTreeMap<NodeId, Option<String>>,
+    edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn new(directed: bool) -> Self {
+        Self { directed, labels: BTreeMap::new(), edges: Vec::new() }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.labels.entry(node_id).or_default();
+    }
+
+    pub fn set_label(&mut self, node_id: NodeId, label: &str) {
+        self.labels.insert(node_id, Some(label.to_string()));
+    }
+
+    // u --> v for a directed graph, u <--> v otherwise
+    pub fn add_edge(&mut self, u: NodeId, v: NodeId) {
+        self.push_edge(u, v, None);
+    }
+
+    pub fn add_weighted_edge(&mut self, u: NodeId, v: NodeId, weight: Weight) {
+        self.push_edge(u, v, Some(weight));
+    }
+
+    fn push_edge(&mut self, u: NodeId, v: NodeId, weight: Option<Weight>) {
+        self.add_node(u);
+        self.add_node(v);
+        self.edges.push(Edge { from: u, to: v, weight });
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        self.labels.keys().copied().collect()
+    }
+
+    pub fn label(&self, node_id: NodeId) -> Option<&str> {
+        self.labels.get(&node_id)?.as_deref()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// The graph in the shape the other graph crates build
+    /// theirs in, undirected edges going both ways. Handy for
+    /// passing straight to e.g. `has_cycle::find_cycle`.
+    pub fn adjacency_list(&self) -> HashMap<NodeId, Vec<NodeId>> {
+        let mut adjacency_list: HashMap<NodeId, Vec<NodeId>> =
+            self.labels.keys().map(|node| (*node, Vec::new())).collect();
+        for edge in &self.edges {
+            adjacency_list.entry(edge.from).or_default().push(edge.to);
+            if !self.directed {
+                adjacency_list.entry(edge.to).or_default().push(edge.from);
+            }
+        }
+        adjacency_list
+    }
+
+    /// Nodes that aren't at either end of any edge
+    fn isolated_nodes(&self) -> Vec<NodeId> {
+        let mut isolated = self.labels.clone();
+        for edge in &self.edges {
+            isolated.remove(&edge.from);
+            isolated.remove(&edge.to);
+        }
+        isolated.into_keys().collect()
+    }
+}
+
+/// What went wrong reading a graph and on which line
+/// (counting from 1)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+fn parse_node(token: &str, line: usize) -> Result<NodeId, ParseError> {
+    token.parse().map_err(|_| {
+        ParseError::new(line, format!("expected a node id, found `{}`", token))
+    })
+}
+
+fn parse_weight(token: &str, line: usize) -> Result<Weight, ParseError> {
+    token.parse().map_err(|_| {
+        ParseError::new(line, format!("expected a weight, found `{}`", token))
+    })
+}
+
+/// Everything before a `#` comment
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A graph using everything the formats have to offer
+    pub(crate) fn example_graph() -> Graph {
+        let mut graph = Graph::new(true);
+        graph.add_edge(0, 1);
+        graph.add_weighted_edge(1, 2, -4);
+        graph.add_weighted_edge(0, 2, 7);
+        graph.add_edge(2, 2);
+        graph.add_node(5);
+        graph.set_label(1, "warehouse \"B\"");
+        graph.set_label(5, "depot");
+        // 0 ---> 1 --(-4)--> 2 <--
+        // |                  ^    |
+        // |                  |    |
+        //  -------(7)--------  ---
+        //
+        // 5
+        graph
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = example_graph();
+        assert_eq!(graph.nodes(), vec![0, 1, 2, 5]);
+        assert_eq!(graph.label(1), Some("warehouse \"B\""));
+        assert_eq!(graph.label(0), None);
+        assert_eq!(graph.label(9), None);
+        assert_eq!(graph.isolated_nodes(), vec![5]);
+        assert_eq!(
+            graph.edges()[1],
+            Edge { from: 1, to: 2, weight: Some(-4) }
+        );
+
+        let adjacency_list = graph.adjacency_list();
+        assert_eq!(adjacency_list[&0], vec![1, 2]);
+        assert_eq!(adjacency_list[&5], vec![]);
+
+        let mut graph = Graph::new(false);
+        graph.add_edge(0, 1);
+        assert_eq!(graph.adjacency_list()[&1], vec![0]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = ParseError::new(3, "expected a node id, found `x`");
+        assert_eq!(error.to_string(), "line 3: expected a node id, found `x`");
+        assert_eq!(parse_node("x", 3), Err(error));
+        assert_eq!(parse_node("-1", 1).unwrap_err().line, 1);
+        assert_eq!(parse_weight("-1", 1), Ok(-1));
+    }
+}
@@ -0,0 +1,506 @@
+//! Graph isomorphism and subgraph matching.
+//!
+//! Both come down to the same backtracking search, in the
+//! spirit of VF2: pattern nodes are mapped one at a time, in
+//! an order where each one has edges to those mapped before
+//! it, and a candidate is only tried if the edges to the
+//! nodes mapped so far line up. For isomorphism, colour
+//! refinement first sorts the nodes of both graphs into
+//! classes that can only map onto each other, which cuts the
+//! candidates down to very few in most graphs.
+//!
+//! Edges are taken the way they point, an undirected graph
+//! having every edge both ways. Parallel edges have to be
+//! matched by as many edges.
+use std::collections::HashMap;
+
+pub use compressed_graph::{Adjacency, CsrGraph, Graph, NodeId};
+
+/// Which node of the second graph each node of the first one
+/// is mapped to
+pub type Mapping = HashMap<NodeId, NodeId>;
+
+/// A graph with its nodes numbered `0..n` and the number of
+/// edges between every pair of them at hand
+struct Indexed {
+    nodes: Vec<NodeId>,
+    out: Vec<Vec<usize>>,
+    into: Vec<Vec<usize>>,
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl Indexed {
+    fn new<G: Adjacency>(graph: &G) -> Self {
+        let nodes = graph.nodes();
+        let mut index = vec![0; graph.node_bound()];
+        for (i, node) in nodes.iter().enumerate() {
+            index[*node] = i;
+        }
+        let mut out = vec![Vec::new(); nodes.len()];
+        let mut into = vec![Vec::new(); nodes.len()];
+        let mut edges = HashMap::new();
+        for (u, node) in nodes.iter().enumerate() {
+            for neighbour in graph.neighbours(*node) {
+                let v = index[*neighbour];
+                out[u].push(v);
+                into[v].push(u);
+                *edges.entry((u, v)).or_insert(0) += 1;
+            }
+        }
+        Self { nodes, out, into, edges }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn edge_count(&self, u: usize, v: usize) -> usize {
+        self.edges.get(&(u, v)).copied().unwrap_or(0)
+    }
+}
+
+/// Colour refinement, also known as 1-dimensional
+/// Weisfeiler-Leman, run on both graphs at once so that the
+/// colours mean the same thing in each.
+///
+/// Every node starts out with the same colour. Then each
+/// round gives a node a new colour made of its old one and
+/// the colours of the nodes its edges go to and come from,
+/// until the number of colours stops growing. Nodes an
+/// isomorphism could map onto each other always end up with
+/// the same colour, the other way round doesn't hold: all
+/// the nodes of a 6-cycle and of two triangles get the same
+/// colour, for instance.
+fn colour_refinement(graphs: [&Indexed; 2]) -> [Vec<usize>; 2] {
+    let mut colours = [vec![0; graphs[0].len()], vec![0; graphs[1].len()]];
+    let mut colour_count = 1;
+    loop {
+        let signature = |g: usize, u: usize| {
+            let graph = graphs[g];
+            let mut out: Vec<usize> =
+                graph.out[u].iter().map(|v| colours[g][*v]).collect();
+            let mut into: Vec<usize> =
+                graph.into[u].iter().map(|v| colours[g][*v]).collect();
+            out.sort_unstable();
+            into.sort_unstable();
+            (colours[g][u], out, into)
+        };
+        let signatures: [Vec<_>; 2] = [
+            (0..graphs[0].len()).map(|u| signature(0, u)).collect(),
+            (0..graphs[1].len()).map(|u| signature(1, u)).collect(),
+        ];
+        let mut distinct: Vec<_> = signatures.iter().flatten().collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+        let next = |g: usize| -> Vec<usize> {
+            signatures[g]
+                .iter()
+                .map(|s| distinct.binary_search(&s).unwrap())
+                .collect()
+        };
+        let refined = [next(0), next(1)];
+        if distinct.len() == colour_count {
+            return refined;
+        }
+        colour_count = distinct.len();
+        colours = refined;
+    }
+}
+
+struct Search<'a> {
+    pattern: &'a Indexed,
+    target: &'a Indexed,
+    // isomorphisms and induced subgraphs need exactly as many
+    // edges between mapped nodes, other subgraphs at least as
+    // many
+    exact: bool,
+    // the colour every node of each graph has to match, if any
+    colours: Option<[Vec<usize>; 2]>,
+    order: Vec<usize>,
+    mapping: Vec<Option<usize>>,
+    used: Vec<bool>,
+    found: Vec<Mapping>,
+    limit: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(pattern: &'a Indexed, target: &'a Indexed, exact: bool) -> Self {
+        Self {
+            pattern,
+            target,
+            exact,
+            colours: None,
+            order: search_order(pattern),
+            mapping: vec![None; pattern.len()],
+            used: vec![false; target.len()],
+            found: Vec::new(),
+            limit: usize::MAX,
+        }
+    }
+
+    fn run(mut self) -> Vec<Mapping> {
+        if self.pattern.len() <= self.target.len() {
+            self.extend(0);
+        }
+        self.found
+    }
+
+    /// Whether pattern node `u` can go to target node `x`
+    /// given what has been mapped so far
+    fn feasible(&self, u: usize, x: usize) -> bool {
+        let (pattern, target) = (self.pattern, self.target);
+        if self.used[x] {
+            return false;
+        }
+        if let Some([pattern_colours, target_colours]) = &self.colours {
+            if pattern_colours[u] != target_colours[x] {
+                return false;
+            }
+        }
+        if target.out[x].len() < pattern.out[u].len()
+            || target.into[x].len() < pattern.into[u].len()
+        {
+            return false;
+        }
+        let fits = |needed: usize, there: usize| {
+            if self.exact {
+                needed == there
+            } else {
+                needed <= there
+            }
+        };
+        // the edges to the nodes mapped so far, and from
+        // them, self loops included
+        let mut mapped =
+            self.order.iter().take_while(|w| self.mapping[**w].is_some());
+        fits(pattern.edge_count(u, u), target.edge_count(x, x))
+            && mapped.all(|w| {
+                let y = self.mapping[*w].unwrap();
+                fits(pattern.edge_count(u, *w), target.edge_count(x, y))
+                    && fits(pattern.edge_count(*w, u), target.edge_count(y, x))
+            })
+    }
+
+    /// Maps the pattern nodes from `order[depth]` on. Returns
+    /// `true` once enough mappings have been found.
+    fn extend(&mut self, depth: usize) -> bool {
+        if depth == self.order.len() {
+            let mapping = self
+                .mapping
+                .iter()
+                .enumerate()
+                .map(|(u, x)| {
+                    (self.pattern.nodes[u], self.target.nodes[x.unwrap()])
+                })
+                .collect();
+            self.found.push(mapping);
+            return self.found.len() >= self.limit;
+        }
+
+        let u = self.order[depth];
+        // a node with an edge to or from one mapped already
+        // can only go to a neighbour of where that one went
+        let (pattern, target) = (self.pattern, self.target);
+        let anchor = self.order[..depth].iter().find_map(|w| {
+            let y = self.mapping[*w].unwrap();
+            if pattern.out[*w].contains(&u) {
+                Some(&target.out[y])
+            } else if pattern.into[*w].contains(&u) {
+                Some(&target.into[y])
+            } else {
+                None
+            }
+        });
+        let mut candidates: Vec<usize> = match anchor {
+            Some(neighbours) => neighbours.clone(),
+            None => (0..target.len()).collect(),
+        };
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        for x in candidates {
+            if !self.feasible(u, x) {
+                continue;
+            }
+            self.mapping[u] = Some(x);
+            self.used[x] = true;
+            if self.extend(depth + 1) {
+                return true;
+            }
+            self.mapping[u] = None;
+            self.used[x] = false;
+        }
+        false
+    }
+}
+
+/// The order the pattern nodes get mapped in: the node with
+/// the most edges first, then always the node with the most
+/// edges to those already picked, most edges overall breaking
+/// ties
+fn search_order(pattern: &Indexed) -> Vec<usize> {
+    let n = pattern.len();
+    let degree: Vec<usize> =
+        (0..n).map(|u| pattern.out[u].len() + pattern.into[u].len()).collect();
+    let mut links = vec![0; n];
+    let mut picked = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    for _ in 0..n {
+        let u = (0..n)
+            .filter(|u| !picked[*u])
+            .max_by_key(|u| (links[*u], degree[*u], n - u))
+            .unwrap();
+        picked[u] = true;
+        order.push(u);
+        for v in pattern.out[u].iter().chain(&pattern.into[u]) {
+            links[*v] += 1;
+        }
+    }
+    order
+}
+
+/// A mapping of the nodes of `g` onto the nodes of `h` that
+/// turns every edge of `g` into an edge of `h` and the other
+/// way round, if there is one
+pub fn isomorphism<G: Adjacency, H: Adjacency>(
+    g: &G,
+    h: &H,
+) -> Option<Mapping> {
+    let (g, h) = (Indexed::new(g), Indexed::new(h));
+    let same_size = g.len() == h.len()
+        && g.out.iter().map(Vec::len).sum::<usize>()
+            == h.out.iter().map(Vec::len).sum::<usize>();
+    if !same_size {
+        return None;
+    }
+    let colours = colour_refinement([&g, &h]);
+    let mut histograms = colours.clone();
+    histograms.iter_mut().for_each(|colours| colours.sort_unstable());
+    if histograms[0] != histograms[1] {
+        return None;
+    }
+
+    let mut search = Search::new(&g, &h, true);
+    search.colours = Some(colours);
+    search.limit = 1;
+    search.run().pop()
+}
+
+pub fn are_isomorphic<G: Adjacency, H: Adjacency>(g: &G, h: &H) -> bool {
+    isomorphism(g, h).is_some()
+}
+
+/// Every place `pattern` turns up inside `target`: mappings
+/// of the pattern nodes onto distinct target nodes such that
+/// every pattern edge has a matching target edge. With
+/// `induced` the target nodes can't have any other edges
+/// between them either.
+///
+/// A pattern that can be mapped onto itself in several ways,
+/// like a triangle in 6 ways, shows up once for each of them at
+/// every place it occurs.
+pub fn subgraph_matches<P: Adjacency, T: Adjacency>(
+    pattern: &P,
+    target: &T,
+    induced: bool,
+) -> Vec<Mapping> {
+    let (pattern, target) = (Indexed::new(pattern), Indexed::new(target));
+    Search::new(&pattern, &target, induced).run()
+}
+
+/// The first of the `subgraph_matches`, without looking for
+/// the others
+pub fn find_subgraph<P: Adjacency, T: Adjacency>(
+    pattern: &P,
+    target: &T,
+    induced: bool,
+) -> Option<Mapping> {
+    let (pattern, target) = (Indexed::new(pattern), Indexed::new(target));
+    let mut search = Search::new(&pattern, &target, induced);
+    search.limit = 1;
+    search.run().pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::{
+        complete, cycle, erdos_renyi, grid, path, GeneratedGraph, Rng,
+    };
+
+    /// The same graph with node `u` renamed `permutation[u]`
+    fn relabel(graph: &GeneratedGraph, permutation: &[NodeId]) -> Graph {
+        let mut relabelled = Graph::new();
+        for node in permutation.iter().take(graph.node_count) {
+            relabelled.add_node(*node);
+        }
+        for (u, v) in &graph.edges {
+            let (u, v) = (permutation[*u], permutation[*v]);
+            if graph.directed {
+                relabelled.add_directed_edge(u, v);
+            } else {
+                relabelled.add_undirected_edge(u, v);
+            }
+        }
+        relabelled
+    }
+
+    /// Whether every edge of `g` has a matching edge in `h`
+    fn preserves_edges(g: &Graph, h: &Graph, mapping: &Mapping) -> bool {
+        g.edges().all(|(u, v)| h.has_edge(mapping[&u], mapping[&v]))
+    }
+
+    #[test]
+    fn test_isomorphism() {
+        let hexagon: Graph = cycle(6, false).build();
+        let relabelled = relabel(&cycle(6, false), &[3, 5, 0, 4, 1, 2]);
+        let mapping = isomorphism(&hexagon, &relabelled).unwrap();
+        assert!(preserves_edges(&hexagon, &relabelled, &mapping));
+
+        // every node has two neighbours in both, which colour
+        // refinement can't tell apart
+        let mut triangles = Graph::new();
+        for (u, v) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)] {
+            triangles.add_undirected_edge(u, v);
+        }
+        assert!(!are_isomorphic(&hexagon, &triangles));
+        assert!(!are_isomorphic(&hexagon, &path(6, false).build::<Graph>()));
+        assert!(are_isomorphic(&Graph::new(), &Graph::new()));
+    }
+
+    #[test]
+    fn test_directed_isomorphism() {
+        let mut g = Graph::new();
+        g.add_directed_edge(0, 1);
+        g.add_directed_edge(1, 2);
+        g.add_directed_edge(1, 2);
+        // 0 ---> 1 ===> 2
+        let mut h = Graph::new();
+        h.add_directed_edge(7, 5);
+        h.add_directed_edge(7, 5);
+        h.add_directed_edge(9, 7);
+        // 9 ---> 7 ===> 5
+        let mapping = isomorphism(&g, &h).unwrap();
+        assert_eq!(mapping, Mapping::from([(0, 9), (1, 7), (2, 5)]));
+
+        let mut h = Graph::new();
+        h.add_directed_edge(9, 7);
+        h.add_directed_edge(9, 7);
+        h.add_directed_edge(7, 5);
+        assert_eq!(isomorphism(&g, &h), None);
+        let reversed = g.reverse();
+        assert!(!are_isomorphic(&g, &reversed));
+    }
+
+    #[test]
+    fn test_isomorphism_fuzz() {
+        let mut rng = Rng::new(7);
+        for seed in 0..100 {
+            let generated = erdos_renyi(12, 0.3, seed % 2 == 0, seed);
+            let graph: Graph = generated.build();
+            let mut permutation: Vec<NodeId> = (0..12).collect();
+            rng.shuffle(&mut permutation);
+            let relabelled = relabel(&generated, &permutation);
+            let mapping = isomorphism(&graph, &relabelled).unwrap();
+            assert!(preserves_edges(&graph, &relabelled, &mapping));
+        }
+    }
+
+    /// Tries every permutation of the nodes of `g`
+    fn brute_force(g: &GeneratedGraph, h: &GeneratedGraph) -> bool {
+        fn permute(
+            g: &GeneratedGraph,
+            h: &Graph,
+            permutation: &mut Vec<NodeId>,
+            k: usize,
+        ) -> bool {
+            if k == permutation.len() {
+                let mut edges: Vec<(NodeId, NodeId)> =
+                    relabel(g, permutation).edges().collect();
+                let mut expected: Vec<(NodeId, NodeId)> = h.edges().collect();
+                edges.sort_unstable();
+                expected.sort_unstable();
+                return edges == expected;
+            }
+            for i in k..permutation.len() {
+                permutation.swap(k, i);
+                if permute(g, h, permutation, k + 1) {
+                    return true;
+                }
+                permutation.swap(k, i);
+            }
+            false
+        }
+        let mut permutation: Vec<NodeId> = (0..g.node_count).collect();
+        permute(g, &h.build(), &mut permutation, 0)
+    }
+
+    #[test]
+    fn test_isomorphism_against_brute_force() {
+        let mut isomorphic = 0;
+        for seed in 0..200 {
+            let g = erdos_renyi(6, 0.4, seed % 2 == 0, seed);
+            let h = erdos_renyi(6, 0.4, seed % 2 == 0, seed + 1000);
+            if g.edges.len() != h.edges.len() {
+                continue;
+            }
+            let expected = brute_force(&g, &h);
+            let graphs: (Graph, Graph) = (g.build(), h.build());
+            assert_eq!(are_isomorphic(&graphs.0, &graphs.1), expected);
+            isomorphic += expected as usize;
+        }
+        assert!(isomorphic > 0);
+    }
+
+    #[test]
+    fn test_subgraph_matches() {
+        let triangle: Graph = cycle(3, false).build();
+        let k4: Graph = complete(4, false).build();
+        // 4 triangles, each one mapped 6 ways
+        let matches = subgraph_matches(&triangle, &k4, false);
+        assert_eq!(matches.len(), 24);
+        assert!(matches.iter().all(|m| preserves_edges(&triangle, &k4, m)));
+        assert_eq!(
+            find_subgraph(&triangle, &grid(4, 4).build::<Graph>(), false),
+            None
+        );
+
+        // a path of three nodes sits in a triangle, but the
+        // triangle has the edge between its ends as well
+        let wedge: Graph = path(3, false).build();
+        assert_eq!(subgraph_matches(&wedge, &triangle, false).len(), 6);
+        assert_eq!(find_subgraph(&wedge, &triangle, true), None);
+        // in a 3 x 3 grid: 4 corners, 4 sides with 3 ways each
+        // and the middle with 6 ways, times 2 directions
+        let grid: Graph = grid(3, 3).build();
+        let matches = subgraph_matches(&wedge, &grid, true);
+        assert_eq!(matches.len(), (4 + 4 * 3 + 6) * 2);
+    }
+
+    #[test]
+    fn test_directed_subgraph() {
+        let mut pattern = Graph::new();
+        pattern.add_directed_edge(0, 1);
+        pattern.add_directed_edge(1, 2);
+        pattern.add_directed_edge(2, 0);
+        let mut target = Graph::new();
+        target.add_directed_edge(10, 11);
+        target.add_directed_edge(11, 12);
+        target.add_directed_edge(12, 13);
+        target.add_directed_edge(13, 11);
+        target.add_directed_edge(10, 12);
+        //          ------------------
+        //         v                  |
+        // 10 ---> 11 ---> 12 ---> 13
+        //  \              ^
+        //   --------------
+        let matches = subgraph_matches(&pattern, &target, false);
+        assert_eq!(matches.len(), 3);
+        let mut images: Vec<NodeId> = matches[0].values().copied().collect();
+        images.sort_unstable();
+        assert_eq!(images, vec![11, 12, 13]);
+        assert!(find_subgraph(&pattern, &target.reverse(), false).is_some());
+        target.remove_directed_edge(13, 11);
+        assert_eq!(find_subgraph(&pattern, &target, false), None);
+    }
+}
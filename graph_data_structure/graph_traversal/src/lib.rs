@@ -0,0 +1,633 @@
+use std::collections::VecDeque;
+
+pub use compressed_graph::{Adjacency, CsrGraph, Graph, NodeId};
+
+/// What a visitor wants the traversal to do next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    /// From `start` or `discover`: don't look at the edges of
+    /// this node. From `tree_edge`: don't follow this edge,
+    /// the node at the other end stays undiscovered. Same as
+    /// `Continue` anywhere else.
+    Prune,
+    /// End the whole traversal right away
+    Stop,
+}
+
+/// Gets told about everything a traversal does. Every event
+/// does nothing by default, so a visitor only implements the
+/// ones it cares about.
+///
+/// For a node the events come in the order `start` (if the
+/// traversal begins there), `discover`, its edges, `finish`.
+/// Depth-first search sorts the edges leading to nodes
+/// already discovered into
+///
+/// - back edges, to a node whose DFS hasn't finished yet, i.e.
+///   an ancestor: these are what close cycles
+/// - forward edges, to a finished descendant
+/// - cross edges, to any other finished node
+///
+/// Breadth-first search reports all of them as
+/// `non_tree_edge`. In an undirected graph every edge shows
+/// up from both ends, the edge back to the parent included.
+pub trait Visitor {
+    /// A new traversal tree begins at `root`
+    fn start(&mut self, _root: NodeId) -> Control {
+        Control::Continue
+    }
+
+    /// `node` is reached for the first time
+    fn discover(&mut self, _node: NodeId) -> Control {
+        Control::Continue
+    }
+
+    /// `v` is about to be discovered through the edge `u --> v`
+    fn tree_edge(&mut self, _u: NodeId, _v: NodeId) -> Control {
+        Control::Continue
+    }
+
+    fn back_edge(&mut self, _u: NodeId, _v: NodeId) -> Control {
+        Control::Continue
+    }
+
+    fn forward_edge(&mut self, _u: NodeId, _v: NodeId) -> Control {
+        Control::Continue
+    }
+
+    fn cross_edge(&mut self, _u: NodeId, _v: NodeId) -> Control {
+        Control::Continue
+    }
+
+    fn non_tree_edge(&mut self, _u: NodeId, _v: NodeId) -> Control {
+        Control::Continue
+    }
+
+    /// Every edge of `node` has been dealt with
+    fn finish(&mut self, _node: NodeId) -> Control {
+        Control::Continue
+    }
+}
+
+/// Depth-first search from each of `roots` in turn, skipping
+/// the roots an earlier search already reached. Pass
+/// `graph.nodes()` to cover the whole graph. Keeps its own
+/// stack rather than recursing, so deep graphs are fine.
+///
+/// Returns `false` if the visitor stopped the traversal.
+pub fn depth_first_visit<G, V>(
+    graph: &G,
+    roots: impl IntoIterator<Item = NodeId>,
+    visitor: &mut V,
+) -> bool
+where
+    G: Adjacency,
+    V: Visitor,
+{
+    let bound = graph.node_bound();
+    let mut discovered = vec![None; bound]; // discovery time
+    let mut finished = vec![false; bound];
+    let mut time = 0;
+    // each node with the index of the next edge to look at
+    let mut stack: Vec<(NodeId, usize)> = Vec::new();
+
+    // discovers `node`, putting it on the stack unless the
+    // visitor prunes it, in which case it is done right away
+    let mut enter = |node: NodeId,
+                     visitor: &mut V,
+                     discovered: &mut Vec<Option<usize>>,
+                     finished: &mut Vec<bool>,
+                     stack: &mut Vec<(NodeId, usize)>| {
+        discovered[node] = Some(time);
+        time += 1;
+        match visitor.discover(node) {
+            Control::Stop => false,
+            Control::Prune => {
+                finished[node] = true;
+                visitor.finish(node) != Control::Stop
+            }
+            Control::Continue => {
+                stack.push((node, 0));
+                true
+            }
+        }
+    };
+
+    for root in roots {
+        if !graph.contains(root) || discovered[root].is_some() {
+            continue;
+        }
+        match visitor.start(root) {
+            Control::Stop => return false,
+            Control::Prune => continue,
+            Control::Continue => {}
+        }
+        if !enter(root, visitor, &mut discovered, &mut finished, &mut stack) {
+            return false;
+        }
+
+        while let Some((node, next_edge)) = stack.last_mut() {
+            let node = *node;
+            let Some(next) = graph.neighbours(node).get(*next_edge).copied()
+            else {
+                stack.pop();
+                finished[node] = true;
+                if visitor.finish(node) == Control::Stop {
+                    return false;
+                }
+                continue;
+            };
+            *next_edge += 1;
+
+            let control = match discovered[next] {
+                None => match visitor.tree_edge(node, next) {
+                    Control::Continue => {
+                        if !enter(
+                            next,
+                            visitor,
+                            &mut discovered,
+                            &mut finished,
+                            &mut stack,
+                        ) {
+                            return false;
+                        }
+                        Control::Continue
+                    }
+                    control => control,
+                },
+                Some(_) if !finished[next] => visitor.back_edge(node, next),
+                Some(time) if discovered[node] < Some(time) => {
+                    visitor.forward_edge(node, next)
+                }
+                Some(_) => visitor.cross_edge(node, next),
+            };
+            if control == Control::Stop {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Breadth-first search from each of `roots` in turn,
+/// skipping the roots an earlier search already reached.
+///
+/// Returns `false` if the visitor stopped the traversal.
+pub fn breadth_first_visit<G, V>(
+    graph: &G,
+    roots: impl IntoIterator<Item = NodeId>,
+    visitor: &mut V,
+) -> bool
+where
+    G: Adjacency,
+    V: Visitor,
+{
+    let mut discovered = vec![false; graph.node_bound()];
+    let mut queue = VecDeque::new();
+
+    // discovers `node`, queueing it unless the visitor prunes
+    // it, in which case it is done right away
+    let enter = |node: NodeId,
+                 visitor: &mut V,
+                 discovered: &mut Vec<bool>,
+                 queue: &mut VecDeque<NodeId>| {
+        discovered[node] = true;
+        match visitor.discover(node) {
+            Control::Stop => false,
+            Control::Prune => visitor.finish(node) != Control::Stop,
+            Control::Continue => {
+                queue.push_back(node);
+                true
+            }
+        }
+    };
+
+    for root in roots {
+        if !graph.contains(root) || discovered[root] {
+            continue;
+        }
+        match visitor.start(root) {
+            Control::Stop => return false,
+            Control::Prune => continue,
+            Control::Continue => {}
+        }
+        if !enter(root, visitor, &mut discovered, &mut queue) {
+            return false;
+        }
+
+        while let Some(node) = queue.pop_front() {
+            for next in graph.neighbours(node) {
+                let control = if discovered[*next] {
+                    visitor.non_tree_edge(node, *next)
+                } else {
+                    match visitor.tree_edge(node, *next) {
+                        Control::Continue => {
+                            if !enter(
+                                *next,
+                                visitor,
+                                &mut discovered,
+                                &mut queue,
+                            ) {
+                                return false;
+                            }
+                            Control::Continue
+                        }
+                        control => control,
+                    }
+                };
+                if control == Control::Stop {
+                    return false;
+                }
+            }
+            if visitor.finish(node) == Control::Stop {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Collects the nodes in the order they are discovered
+#[derive(Clone, Debug, Default)]
+pub struct DiscoveryOrder(pub Vec<NodeId>);
+
+impl Visitor for DiscoveryOrder {
+    fn discover(&mut self, node: NodeId) -> Control {
+        self.0.push(node);
+        Control::Continue
+    }
+}
+
+pub fn depth_first_search<G: Adjacency>(
+    graph: &G,
+    start_node: NodeId,
+) -> Vec<NodeId> {
+    let mut order = DiscoveryOrder::default();
+    depth_first_visit(graph, [start_node], &mut order);
+    order.0
+}
+
+pub fn breadth_first_search<G: Adjacency>(
+    graph: &G,
+    start_node: NodeId,
+) -> Vec<NodeId> {
+    let mut order = DiscoveryOrder::default();
+    breadth_first_visit(graph, [start_node], &mut order);
+    order.0
+}
+
+/// Finds a cycle in a directed graph: the first back edge
+/// closes one, made of the nodes on the DFS path from the
+/// node it points to down to the node it comes from.
+#[derive(Clone, Debug, Default)]
+pub struct CycleFinder {
+    path: Vec<NodeId>,
+    pub cycle: Option<Vec<NodeId>>,
+}
+
+impl Visitor for CycleFinder {
+    fn discover(&mut self, node: NodeId) -> Control {
+        self.path.push(node);
+        Control::Continue
+    }
+
+    fn back_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+        let start = self.path.iter().rposition(|node| *node == v).unwrap();
+        debug_assert_eq!(self.path.last(), Some(&u));
+        self.cycle = Some(self.path[start..].to_vec());
+        Control::Stop
+    }
+
+    fn finish(&mut self, _node: NodeId) -> Control {
+        self.path.pop();
+        Control::Continue
+    }
+}
+
+/// A cycle of a directed graph, in the order the edges run,
+/// if there is one
+pub fn find_cycle<G: Adjacency>(graph: &G) -> Option<Vec<NodeId>> {
+    let mut finder = CycleFinder::default();
+    depth_first_visit(graph, graph.nodes(), &mut finder);
+    finder.cycle
+}
+
+pub fn has_cycle<G: Adjacency>(graph: &G) -> bool {
+    find_cycle(graph).is_some()
+}
+
+/// Counts the nodes of every traversal tree. On an undirected
+/// graph those are the connected components.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentSizes(pub Vec<usize>);
+
+impl Visitor for ComponentSizes {
+    fn start(&mut self, _root: NodeId) -> Control {
+        self.0.push(0);
+        Control::Continue
+    }
+
+    fn discover(&mut self, _node: NodeId) -> Control {
+        *self.0.last_mut().unwrap() += 1;
+        Control::Continue
+    }
+}
+
+/// Size of every connected component of an undirected graph,
+/// in the order of their smallest node
+pub fn component_sizes<G: Adjacency>(graph: &G) -> Vec<usize> {
+    let mut sizes = ComponentSizes::default();
+    breadth_first_visit(graph, graph.nodes(), &mut sizes);
+    sizes.0
+}
+
+/// Remembers how every node was reached and stops once it
+/// gets to `dest`
+#[derive(Clone, Debug)]
+pub struct PathFinder {
+    dest: NodeId,
+    parent: Vec<Option<NodeId>>,
+    found: bool,
+}
+
+impl PathFinder {
+    pub fn new<G: Adjacency>(graph: &G, dest: NodeId) -> Self {
+        Self { dest, parent: vec![None; graph.node_bound()], found: false }
+    }
+
+    /// The path from the root the traversal started at to
+    /// `dest`, if it was found
+    pub fn path(&self) -> Option<Vec<NodeId>> {
+        if !self.found {
+            return None;
+        }
+        let mut path = vec![self.dest];
+        while let Some(parent) = self.parent[*path.last().unwrap()] {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Visitor for PathFinder {
+    fn tree_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+        self.parent[v] = Some(u);
+        Control::Continue
+    }
+
+    fn discover(&mut self, node: NodeId) -> Control {
+        if node == self.dest {
+            self.found = true;
+            return Control::Stop;
+        }
+        Control::Continue
+    }
+}
+
+/// A path with the fewest edges from `start` to `dest`
+pub fn find_path<G: Adjacency>(
+    graph: &G,
+    start: NodeId,
+    dest: NodeId,
+) -> Option<Vec<NodeId>> {
+    if !graph.contains(dest) {
+        return None;
+    }
+    let mut finder = PathFinder::new(graph, dest);
+    breadth_first_visit(graph, [start], &mut finder);
+    finder.path()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes every event down
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        prune: Option<NodeId>,
+        stop: Option<NodeId>,
+    }
+
+    impl Recorder {
+        fn record(&mut self, event: String, node: NodeId) -> Control {
+            self.events.push(event);
+            if self.stop == Some(node) {
+                Control::Stop
+            } else if self.prune == Some(node) {
+                Control::Prune
+            } else {
+                Control::Continue
+            }
+        }
+    }
+
+    impl Visitor for Recorder {
+        fn start(&mut self, root: NodeId) -> Control {
+            self.events.push(format!("start {}", root));
+            Control::Continue
+        }
+
+        fn discover(&mut self, node: NodeId) -> Control {
+            self.record(format!("discover {}", node), node)
+        }
+
+        fn tree_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+            self.events.push(format!("tree {} {}", u, v));
+            Control::Continue
+        }
+
+        fn back_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+            self.events.push(format!("back {} {}", u, v));
+            Control::Continue
+        }
+
+        fn forward_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+            self.events.push(format!("forward {} {}", u, v));
+            Control::Continue
+        }
+
+        fn cross_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+            self.events.push(format!("cross {} {}", u, v));
+            Control::Continue
+        }
+
+        fn non_tree_edge(&mut self, u: NodeId, v: NodeId) -> Control {
+            self.events.push(format!("non-tree {} {}", u, v));
+            Control::Continue
+        }
+
+        fn finish(&mut self, node: NodeId) -> Control {
+            self.events.push(format!("finish {}", node));
+            Control::Continue
+        }
+    }
+
+    fn example_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(3, 2);
+        // 0 ---> 1
+        // ^ \    |
+        // |  \   v
+        //  ----> 2 <--- 3
+        graph
+    }
+
+    #[test]
+    fn test_depth_first_events() {
+        let graph = CsrGraph::from(&example_graph());
+        let mut recorder = Recorder::default();
+        assert!(depth_first_visit(&graph, graph.nodes(), &mut recorder));
+        assert_eq!(
+            recorder.events,
+            vec![
+                "start 0",
+                "discover 0",
+                "tree 0 1",
+                "discover 1",
+                "tree 1 2",
+                "discover 2",
+                "back 2 0",
+                "finish 2",
+                "finish 1",
+                "forward 0 2",
+                "finish 0",
+                "start 3",
+                "discover 3",
+                "cross 3 2",
+                "finish 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breadth_first_events() {
+        let graph = example_graph();
+        let mut recorder = Recorder::default();
+        assert!(breadth_first_visit(&graph, [0], &mut recorder));
+        assert_eq!(
+            recorder.events,
+            vec![
+                "start 0",
+                "discover 0",
+                "tree 0 1",
+                "discover 1",
+                "tree 0 2",
+                "discover 2",
+                "finish 0",
+                "non-tree 1 2",
+                "finish 1",
+                "non-tree 2 0",
+                "finish 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prune_and_stop() {
+        let graph = example_graph();
+        let mut recorder = Recorder { prune: Some(1), ..Default::default() };
+        depth_first_visit(&graph, [0], &mut recorder);
+        assert_eq!(
+            recorder.events,
+            vec![
+                "start 0",
+                "discover 0",
+                "tree 0 1",
+                "discover 1",
+                "finish 1",
+                "tree 0 2",
+                "discover 2",
+                "back 2 0",
+                "finish 2",
+                "finish 0",
+            ]
+        );
+
+        let mut recorder = Recorder { stop: Some(2), ..Default::default() };
+        assert!(!breadth_first_visit(&graph, [0, 3], &mut recorder));
+        assert_eq!(recorder.events.last().unwrap(), "discover 2");
+        assert!(!recorder.events.contains(&"start 3".to_string()));
+    }
+
+    #[test]
+    fn test_search_order() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(2, 4);
+        graph.add_directed_edge(3, 5);
+        // 0 ---> 1
+        // |      |
+        // v      v
+        // 2      3
+        // |      |
+        // v      v
+        // 4      5
+        assert_eq!(depth_first_search(&graph, 0), vec![0, 1, 3, 5, 2, 4]);
+        assert_eq!(breadth_first_search(&graph, 0), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            depth_first_search(&graph, 0),
+            compressed_graph::depth_first_search(&graph, 0)
+        );
+        assert_eq!(depth_first_search(&graph, 3), vec![3, 5]);
+        assert_eq!(depth_first_search(&graph, 7), vec![]);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let graph = example_graph();
+        assert_eq!(find_cycle(&graph), Some(vec![0, 1, 2]));
+
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(3, 3);
+        assert_eq!(find_cycle(&graph), Some(vec![3]));
+        graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 2);
+        assert!(!has_cycle(&CsrGraph::from(&graph)));
+    }
+
+    #[test]
+    fn test_components_and_paths() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(0, 3);
+        graph.add_undirected_edge(4, 5);
+        graph.add_node(6);
+        // 0 <--> 1 <--> 2    4 <--> 5    6
+        // ^             ^
+        // |             |
+        // v             v
+        // 3 <-----------
+        let csr = CsrGraph::from(&graph);
+        assert_eq!(component_sizes(&csr), vec![4, 2, 1]);
+        assert_eq!(
+            component_sizes(&csr),
+            compressed_graph::connected_components(&csr)
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(find_path(&csr, 0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(find_path(&csr, 3, 1), Some(vec![3, 2, 1]));
+        assert_eq!(find_path(&csr, 6, 6), Some(vec![6]));
+        assert_eq!(find_path(&csr, 0, 5), None);
+        assert_eq!(find_path(&csr, 0, 9), None);
+    }
+}
@@ -1,6 +1,14 @@
+//! Cycle detection for directed and undirected graphs.
+//!
+//! The functions here work on any adjacency list keyed by
+//! node, so they serve this crate's `Graph` as well as the
+//! plain `HashMap<u32, Vec<u32>>` graphs used by
+//! `detect_cycle` and `prereqs_possible`. A node that only
+//! shows up as a neighbour is treated as a node without
+//! neighbours rather than a reason to panic.
 use std::{
     collections::{HashMap, HashSet},
-    usize,
+    hash::Hash,
 };
 
 pub type NodeId = usize;
@@ -11,25 +19,35 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             adjacency_list: HashMap::new(),
         }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    pub fn adjacency_list(&self) -> &HashMap<NodeId, Vec<NodeId>> {
+        &self.adjacency_list
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -37,60 +55,262 @@ impl Graph {
 /// the adjacency list of a directed graph. The
 /// function should return a boolean indicating whether
 /// or not the graph contains a cycle.
+pub fn has_cycle(graph: Graph) -> bool {
+    find_cycle(&graph.adjacency_list).is_some()
+}
+
+fn neighbours<'a, N: Eq + Hash>(adjacency_list: &'a HashMap<N, Vec<N>>, node: &N) -> &'a [N] {
+    adjacency_list.get(node).map_or(&[], |n| n.as_slice())
+}
+
+fn sorted_nodes<N: Copy + Ord>(adjacency_list: &HashMap<N, Vec<N>>) -> Vec<N> {
+    let mut nodes: Vec<N> = adjacency_list.keys().copied().collect();
+    nodes.sort_unstable();
+    nodes
+}
+
+/// Returns the nodes of a cycle in a directed graph, in the
+/// order the edges run (the last node has an edge back to
+/// the first), or `None` if the graph is acyclic.
 ///
-/// white-grey-black cycle detection algorithm
-fn has_cycle(graph: Graph) -> bool {
+/// white-grey-black cycle detection algorithm. The DFS keeps
+/// its own stack rather than recursing, as a long chain of
+/// dependencies would overflow the call stack.
+pub fn find_cycle<N: Copy + Ord + Hash>(adjacency_list: &HashMap<N, Vec<N>>) -> Option<Vec<N>> {
     // white means not visited yet (default)
-    let mut visiting = HashSet::new(); // grey
+    let mut visiting = HashSet::new(); // grey, the nodes on `path`
     let mut visited = HashSet::new(); // black
+                                      // the path from the DFS root, each node with how many of
+                                      // its neighbours we've been through
+    let mut path: Vec<(N, usize)> = Vec::new();
+
+    for start_node in sorted_nodes(adjacency_list) {
+        if visited.contains(&start_node) {
+            continue;
+        }
+        visiting.insert(start_node);
+        path.push((start_node, 0));
+
+        while let Some((current_node, next)) = path.last().copied() {
+            let Some(neighbour) = neighbours(adjacency_list, &current_node).get(next) else {
+                // We have processed `current_node`
+                // so we are no longer `visiting` it
+                // but it is now `visited`
+                path.pop();
+                visiting.remove(&current_node);
+                visited.insert(current_node);
+                continue;
+            };
+            path.last_mut().unwrap().1 += 1;
 
-    for start_node in &graph.adjacency_list {
-        if detect_cycle(&graph, *start_node.0, &mut visiting, &mut visited) {
-            return true;
+            // If this node is one we are `visiting` we've
+            // come back to it, hence a cycle, made of
+            // everything on the path since that node. Only
+            // now is it worth looking for it on the path.
+            if visiting.contains(neighbour) {
+                let i = path.iter().position(|(n, _)| n == neighbour).unwrap();
+                return Some(path[i..].iter().map(|(n, _)| *n).collect());
+            }
+            if !visited.contains(neighbour) {
+                visiting.insert(*neighbour);
+                path.push((*neighbour, 0));
+            }
         }
     }
-    false
+    None
 }
 
-fn detect_cycle(
-    graph: &Graph,
-    current_node: NodeId,
-    visiting: &mut HashSet<NodeId>,
-    visited: &mut HashSet<NodeId>,
-) -> bool {
-    // I've visited this node before
-    // so no need to go through it again
-    // thus breaking the recursion
-    if visited.contains(&current_node) {
-        return false;
-    };
+/// Returns the nodes of a cycle in an undirected graph (every
+/// edge stored in both directions), or `None` if the graph is
+/// a forest.
+///
+/// In an undirected graph every edge leads straight back to
+/// where we came from so a plain DFS would call each edge a
+/// cycle. We skip the edge back to the parent, once: a second
+/// edge between the same two nodes is a real cycle.
+pub fn find_undirected_cycle<N: Copy + Ord + Hash>(
+    adjacency_list: &HashMap<N, Vec<N>>,
+) -> Option<Vec<N>> {
+    let mut visited = HashSet::new();
+    let mut on_path = HashSet::new();
+    let mut path: Vec<PathNode<N>> = Vec::new();
+
+    for start_node in sorted_nodes(adjacency_list) {
+        if !visited.insert(start_node) {
+            continue;
+        }
+        on_path.insert(start_node);
+        path.push(PathNode::new(start_node, None));
 
-    // If I find this node in `visiting` means
-    // I've traversed this node before and
-    // therefore I've come back to this node
-    // hence a cycle
-    if visiting.contains(&current_node) {
-        return true;
+        while let Some(top) = path.last_mut() {
+            let current_node = top.node;
+            let Some(neighbour) = neighbours(adjacency_list, &current_node).get(top.next) else {
+                path.pop();
+                on_path.remove(&current_node);
+                continue;
+            };
+            top.next += 1;
+            if Some(*neighbour) == top.parent && !top.skipped_parent {
+                top.skipped_parent = true;
+                continue;
+            }
+            // an already visited neighbour is always on the
+            // current path in an undirected DFS, anything
+            // else would have been explored from it
+            if on_path.contains(neighbour) {
+                let i = path.iter().position(|p| p.node == *neighbour).unwrap();
+                return Some(path[i..].iter().map(|p| p.node).collect());
+            }
+            if visited.insert(*neighbour) {
+                on_path.insert(*neighbour);
+                path.push(PathNode::new(*neighbour, Some(current_node)));
+            }
+        }
     }
+    None
+}
 
-    visiting.insert(current_node);
+/// A node on the path of `find_undirected_cycle`'s DFS
+struct PathNode<N> {
+    node: N,
+    parent: Option<N>,
+    // how many of its neighbours we've been through
+    next: usize,
+    skipped_parent: bool,
+}
 
-    for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
-        if detect_cycle(graph, *neighbour, visiting, visited) {
-            return true;
+impl<N> PathNode<N> {
+    fn new(node: N, parent: Option<N>) -> Self {
+        Self {
+            node,
+            parent,
+            next: 0,
+            skipped_parent: false,
         }
     }
+}
 
-    // We have processed `current_node`
-    // so we are no longer `visiting` it
-    // but it is now `visited`
-    visiting.remove(&current_node);
-    visited.insert(current_node);
+/// Every elementary cycle (no node repeated) of a directed
+/// graph, using Johnson's algorithm. Each cycle starts at its
+/// smallest node. There can be exponentially many of them, so
+/// keep this to small graphs.
+///
+/// Taking the nodes in order, we look for the cycles through
+/// `start` that only use nodes larger than it (smaller ones
+/// have had all their cycles listed already), within the
+/// strongly connected component of `start`. A node is
+/// `blocked` while we know it can't lead back to `start`,
+/// and `blocked_by` remembers who to unblock along with it
+/// when that changes.
+pub fn elementary_cycles<N: Copy + Ord + Hash>(adjacency_list: &HashMap<N, Vec<N>>) -> Vec<Vec<N>> {
+    let mut cycles = Vec::new();
 
-    false
+    for start in sorted_nodes(adjacency_list) {
+        let component = component_of(adjacency_list, start);
+        let mut search = Johnson {
+            adjacency_list,
+            component: &component,
+            start,
+            stack: Vec::new(),
+            blocked: HashSet::new(),
+            blocked_by: HashMap::new(),
+            cycles: &mut cycles,
+        };
+        search.circuit(start);
+    }
+
+    cycles
+}
+
+/// The strongly connected component of `start` in the graph
+/// made of the nodes no smaller than `start`: the nodes that
+/// can both be reached from `start` and reach it.
+fn component_of<N: Copy + Ord + Hash>(adjacency_list: &HashMap<N, Vec<N>>, start: N) -> HashSet<N> {
+    let mut reverse: HashMap<N, Vec<N>> = HashMap::new();
+    for (node, neighbours) in adjacency_list {
+        for neighbour in neighbours {
+            reverse.entry(*neighbour).or_default().push(*node);
+        }
+    }
+
+    let reach = |adjacency_list: &HashMap<N, Vec<N>>| {
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for neighbour in neighbours(adjacency_list, &node) {
+                if *neighbour >= start && seen.insert(*neighbour) {
+                    stack.push(*neighbour);
+                }
+            }
+        }
+        seen
+    };
+
+    let forward = reach(adjacency_list);
+    let backward = reach(&reverse);
+    forward.intersection(&backward).copied().collect()
+}
+
+struct Johnson<'a, N> {
+    adjacency_list: &'a HashMap<N, Vec<N>>,
+    component: &'a HashSet<N>,
+    start: N,
+    stack: Vec<N>,
+    blocked: HashSet<N>,
+    blocked_by: HashMap<N, HashSet<N>>,
+    cycles: &'a mut Vec<Vec<N>>,
+}
+
+impl<N: Copy + Ord + Hash> Johnson<'_, N> {
+    fn circuit(&mut self, node: N) -> bool {
+        let mut found_cycle = false;
+        self.stack.push(node);
+        self.blocked.insert(node);
+
+        // parallel edges would list the same cycle twice
+        let mut next_nodes: Vec<N> = neighbours(self.adjacency_list, &node)
+            .iter()
+            .copied()
+            .filter(|n| self.component.contains(n))
+            .collect();
+        next_nodes.sort_unstable();
+        next_nodes.dedup();
+
+        for next in &next_nodes {
+            if *next == self.start {
+                self.cycles.push(self.stack.clone());
+                found_cycle = true;
+            } else if !self.blocked.contains(next) && self.circuit(*next) {
+                found_cycle = true;
+            }
+        }
+
+        if found_cycle {
+            self.unblock(node);
+        } else {
+            // stay blocked until one of the neighbours
+            // finds its way back to `start`
+            for next in next_nodes {
+                self.blocked_by.entry(next).or_default().insert(node);
+            }
+        }
+
+        self.stack.pop();
+        found_cycle
+    }
+
+    fn unblock(&mut self, node: N) {
+        self.blocked.remove(&node);
+        for other in self.blocked_by.remove(&node).unwrap_or_default() {
+            if self.blocked.contains(&other) {
+                self.unblock(other);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -266,4 +486,131 @@ mod tests {
         let contains_cycle = has_cycle(graph);
         assert_eq!(contains_cycle, true);
     }
+
+    #[test]
+    fn test_find_cycle() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 1);
+        // 0 ---> 1 --> 2
+        //        ^     |
+        //        |     |
+        //        3 <-- v
+        let cycle = find_cycle(graph.adjacency_list());
+        assert_eq!(cycle, Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_dangling_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        // 0 ---> 1
+        // |
+        // v
+        // 2
+        // neither 1 nor 2 got an entry of their own
+        assert!(!has_cycle(graph.clone()));
+        assert_eq!(find_undirected_cycle(graph.adjacency_list()), None);
+        assert!(elementary_cycles(graph.adjacency_list()).is_empty());
+    }
+
+    #[test]
+    fn test_undirected_cycle() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(1, 3);
+        // 0 <--> 1 <--> 2
+        //        ^
+        //        |
+        //        v
+        //        3
+        assert_eq!(find_undirected_cycle(graph.adjacency_list()), None);
+        // it would be a cycle if the edges were one-way
+        assert!(find_cycle(graph.adjacency_list()).is_some());
+
+        graph.add_undirected_edge(3, 2);
+        // 0 <--> 1 <--> 2
+        //        ^      ^
+        //        |      |
+        //        v      |
+        //        3 <----
+        let cycle = find_undirected_cycle(graph.adjacency_list());
+        assert_eq!(cycle, Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_undirected_parallel_edges() {
+        let mut graph = Graph::new();
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 1);
+        let cycle = find_undirected_cycle(graph.adjacency_list());
+        assert_eq!(cycle, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_elementary_cycles() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(3, 0);
+        graph.add_directed_edge(2, 2);
+        graph.add_directed_edge(3, 4);
+        graph.add_directed_edge(4, 3);
+        //  ------------------
+        // |                  |
+        // v                  |
+        // 0 ---> 1 ---> 2 ---
+        // ^      |      ^ |
+        // |      v      |_|
+        //  ----- 3 <--> 4
+        let mut cycles = elementary_cycles(graph.adjacency_list());
+        cycles.sort();
+        assert_eq!(
+            cycles,
+            vec![vec![0, 1, 2], vec![0, 1, 3], vec![2], vec![3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // deep enough to overflow the call stack if each node
+        // took a recursive call
+        let mut graph = Graph::new();
+        for node in 0..100_000 {
+            graph.add_directed_edge(node, node + 1);
+        }
+        assert_eq!(find_cycle(graph.adjacency_list()), None);
+        graph.add_directed_edge(100_000, 0);
+        let cycle = find_cycle(graph.adjacency_list()).unwrap();
+        assert_eq!(cycle.len(), 100_001);
+
+        let mut graph = Graph::new();
+        for node in 0..100_000 {
+            graph.add_undirected_edge(node, node + 1);
+        }
+        assert_eq!(find_undirected_cycle(graph.adjacency_list()), None);
+        graph.add_undirected_edge(100_000, 0);
+        let cycle = find_undirected_cycle(graph.adjacency_list()).unwrap();
+        assert_eq!(cycle.len(), 100_001);
+    }
 }
@@ -1,33 +1,68 @@
-use std::{
-    collections::{HashMap, HashSet},
-    usize,
-};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub type NodeId = usize;
 
 #[derive(Clone, Debug)]
 pub struct Graph {
     adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+    // the same edges pointing the other way (v --> u for
+    // every u --> v) so that we can also search backwards
+    // from a destination node
+    reverse_adjacency_list: HashMap<NodeId, Vec<NodeId>>,
 }
 
 impl Graph {
-    fn new() -> Self {
-        Self { adjacency_list: HashMap::new() }
+    pub fn new() -> Self {
+        Self {
+            adjacency_list: HashMap::new(),
+            reverse_adjacency_list: HashMap::new(),
+        }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+        self.reverse_adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.reverse_adjacency_list.entry(v).or_default().push(u);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_directed_edge(u, v);
+        self.add_directed_edge(v, u);
+    }
+
+    /// The nodes `node` has an edge to. A node that only ever
+    /// shows up at the end of a directed edge has no entry of
+    /// its own, it is treated as a node without neighbours.
+    pub fn neighbours(&self, node: &NodeId) -> &[NodeId] {
+        self.adjacency_list.get(node).map_or(&[], |n| n.as_slice())
+    }
+
+    /// All the nodes of the graph in ascending order, those
+    /// without an entry of their own included
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = self
+            .adjacency_list
+            .iter()
+            .flat_map(|(node, neighbours)| {
+                std::iter::once(node).chain(neighbours)
+            })
+            .copied()
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -36,7 +71,7 @@ impl Graph {
 /// indicating whether or not there exists a directed path between the source
 /// and destination nodes.
 /// Depth-first search algorithm
-fn has_path(graph: Graph, start_node: NodeId, dest_node: NodeId) -> bool {
+pub fn has_path(graph: Graph, start_node: NodeId, dest_node: NodeId) -> bool {
     let mut visited = HashSet::new();
     has_path_helper(&graph, start_node, dest_node, &mut visited)
 }
@@ -51,7 +86,7 @@ fn has_path_helper(
         return true;
     }
     visited.insert(current_node);
-    for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
+    for neighbour in graph.neighbours(&current_node) {
         if !visited.contains(neighbour)
             && has_path_helper(graph, *neighbour, dest_node, visited)
         {
@@ -61,7 +96,293 @@ fn has_path_helper(
     false
 }
 
+/// Returns the nodes of a path from `start_node` to
+/// `dest_node` (both included), or `None` if there is no such
+/// path.
+///
+/// Unlike the depth-first search of `has_path` this is a
+/// breadth-first search: for every node we remember the node
+/// we reached it from (its predecessor), so the path found is
+/// also one with the fewest edges. It is the same search as
+/// `shortest_path::shortest_path_to`, repeated here so that
+/// the crate doesn't depend on that one.
+pub fn find_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<Vec<NodeId>> {
+    let mut predecessor: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut visited = HashSet::new();
+    visited.insert(start_node);
+    let mut queue = VecDeque::new();
+    queue.push_back(start_node);
+
+    while let Some(current_node) = queue.pop_front() {
+        if current_node == dest_node {
+            let mut path = vec![current_node];
+            let mut node = current_node;
+            while let Some(previous) = predecessor.get(&node) {
+                path.push(*previous);
+                node = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for neighbour in graph.neighbours(&current_node) {
+            if visited.insert(*neighbour) {
+                predecessor.insert(*neighbour, current_node);
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+    None
+}
+
+/// Same question as `has_path`, answered by
+/// `bidirectional_find_path`
+pub fn bidirectional_has_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> bool {
+    bidirectional_find_path(graph, start_node, dest_node).is_some()
+}
+
+/// Same as `find_path` but the search runs from both ends at
+/// once, the backward one following the edges in reverse, and
+/// stops as soon as the two meet. See
+/// `shortest_path::bidirectional_shortest_path` for why that
+/// visits far fewer nodes.
+///
+/// Any path will do here, so we stop at the first node both
+/// searches have reached instead of looking for the best
+/// meeting point. The path found isn't always the shortest.
+pub fn bidirectional_find_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<Vec<NodeId>> {
+    if start_node == dest_node {
+        return Some(vec![start_node]);
+    }
+
+    // node -> predecessor on each side
+    let mut forward = HashMap::from([(start_node, None)]);
+    let mut backward = HashMap::from([(dest_node, None)]);
+    let mut forward_frontier = vec![start_node];
+    let mut backward_frontier = vec![dest_node];
+
+    while !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+        // grow the smaller of the two frontiers by a level
+        let meeting = if forward_frontier.len() <= backward_frontier.len() {
+            expand_level(
+                &graph.adjacency_list,
+                &mut forward_frontier,
+                &mut forward,
+                &backward,
+            )
+        } else {
+            expand_level(
+                &graph.reverse_adjacency_list,
+                &mut backward_frontier,
+                &mut backward,
+                &forward,
+            )
+        };
+
+        if let Some(node) = meeting {
+            let mut path = walk_back(&forward, node);
+            path.reverse();
+            path.extend(walk_back(&backward, node).into_iter().skip(1));
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Replaces `frontier` with the nodes one edge further that
+/// this side hasn't reached yet. Returns the first of them the
+/// other side has reached already, if any.
+fn expand_level(
+    adjacency_list: &HashMap<NodeId, Vec<NodeId>>,
+    frontier: &mut Vec<NodeId>,
+    this_side: &mut HashMap<NodeId, Option<NodeId>>,
+    other_side: &HashMap<NodeId, Option<NodeId>>,
+) -> Option<NodeId> {
+    let mut next_frontier = Vec::new();
+    for current_node in frontier.iter() {
+        for neighbour in adjacency_list.get(current_node).into_iter().flatten()
+        {
+            if this_side.contains_key(neighbour) {
+                continue;
+            }
+            this_side.insert(*neighbour, Some(*current_node));
+            if other_side.contains_key(neighbour) {
+                return Some(*neighbour);
+            }
+            next_frontier.push(*neighbour);
+        }
+    }
+    *frontier = next_frontier;
+    None
+}
+
+/// Follows the predecessors from `node` back to where that
+/// side of the search started
+fn walk_back(
+    side: &HashMap<NodeId, Option<NodeId>>,
+    node: NodeId,
+) -> Vec<NodeId> {
+    let mut path = vec![node];
+    let mut current_node = node;
+    while let Some(Some(previous)) = side.get(&current_node) {
+        path.push(*previous);
+        current_node = *previous;
+    }
+    path
+}
+
+/// Which nodes can reach which, worked out once so that
+/// every question after that is a lookup.
+///
+/// Every node gets a row of bits, one per node, bit `j` of
+/// row `i` being set when there is a path from the `i`th node
+/// to the `j`th. A node always reaches itself, the same as
+/// `has_path` says. The rows take `n * n / 8` bytes, about
+/// 12 MB for 10,000 nodes.
+#[derive(Clone, Debug)]
+pub struct TransitiveClosure {
+    nodes: Vec<NodeId>,
+    index: HashMap<NodeId, usize>,
+    // `u64`s per row
+    words: usize,
+    rows: Vec<u64>,
+}
+
+impl TransitiveClosure {
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
+    /// Whether there is a path from `u` to `v`
+    pub fn reachable(&self, u: NodeId, v: NodeId) -> bool {
+        match (self.index.get(&u), self.index.get(&v)) {
+            (Some(i), Some(j)) => self.bit(*i, *j),
+            _ => u == v,
+        }
+    }
+
+    /// The nodes `u` can reach in ascending order, `u` itself
+    /// included
+    pub fn reachable_from(&self, u: NodeId) -> Vec<NodeId> {
+        let Some(i) = self.index.get(&u) else {
+            return Vec::new();
+        };
+        (0..self.nodes.len())
+            .filter(|j| self.bit(*i, *j))
+            .map(|j| self.nodes[j])
+            .collect()
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.rows[i * self.words..(i + 1) * self.words]
+    }
+
+    fn bit(&self, i: usize, j: usize) -> bool {
+        self.row(i)[j / 64] & (1 << (j % 64)) != 0
+    }
+}
+
+/// Searches the graph from every node, the row of the start
+/// node serving as the visited set, so that when the search
+/// is done it holds everything reachable. O(n * (n + e))
+/// time.
+pub fn transitive_closure(graph: &Graph) -> TransitiveClosure {
+    let nodes = graph.nodes();
+    let index: HashMap<NodeId, usize> =
+        nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+    let words = nodes.len().div_ceil(64);
+    let mut rows = vec![0; nodes.len() * words];
+    let mut stack = Vec::new();
+    for (i, row) in rows.chunks_mut(words.max(1)).enumerate() {
+        mark_reachable(graph, &index, nodes[i], row, &mut stack);
+    }
+    TransitiveClosure { nodes, index, words, rows }
+}
+
+// With an explicit stack rather than recursion, a long chain
+// would otherwise overflow the call stack. `stack` is only
+// passed in so its allocation is reused from row to row.
+fn mark_reachable(
+    graph: &Graph,
+    index: &HashMap<NodeId, usize>,
+    start_node: NodeId,
+    row: &mut [u64],
+    stack: &mut Vec<NodeId>,
+) {
+    let i = index[&start_node];
+    row[i / 64] |= 1 << (i % 64);
+    stack.push(start_node);
+    while let Some(current_node) = stack.pop() {
+        for neighbour in graph.neighbours(&current_node) {
+            let j = index[neighbour];
+            if row[j / 64] & (1 << (j % 64)) == 0 {
+                row[j / 64] |= 1 << (j % 64);
+                stack.push(*neighbour);
+            }
+        }
+    }
+}
+
+/// The transitive reduction of a directed acyclic graph: the
+/// fewest edges that still leave every node able to reach the
+/// same nodes. An edge u --> v goes when v can also be
+/// reached through another of u's neighbours, and so do
+/// parallel edges. All the nodes stay.
+///
+/// Returns `None` if the graph has a cycle, where the
+/// reduction is no longer unique.
+pub fn transitive_reduction(graph: &Graph) -> Option<Graph> {
+    let closure = transitive_closure(graph);
+    let mut reduction = Graph::new();
+    for node in closure.nodes() {
+        reduction.add_node(*node);
+    }
+    for node in closure.nodes() {
+        let neighbours = graph.neighbours(node);
+        // a cycle goes through u --> v when v reaches u
+        if neighbours.iter().any(|v| closure.reachable(*v, *node)) {
+            return None;
+        }
+
+        // everything reachable from a neighbour through at least
+        // one more edge. Without cycles no node reaches itself
+        // that way, so it is the neighbour's row minus its own
+        // bit.
+        let mut covered = vec![0u64; closure.words];
+        for v in neighbours {
+            let j = closure.index[v];
+            let words = covered.iter_mut().zip(closure.row(j));
+            for (k, (word, reached)) in words.enumerate() {
+                let own = if k == j / 64 { 1 << (j % 64) } else { 0 };
+                *word |= reached & !own;
+            }
+        }
+        for v in neighbours {
+            let j = closure.index[v];
+            let redundant = covered[j / 64] & (1 << (j % 64)) != 0;
+            if !redundant && !reduction.neighbours(node).contains(v) {
+                reduction.add_directed_edge(*node, *v);
+            }
+        }
+    }
+    Some(reduction)
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -148,4 +469,176 @@ mod tests {
         let result = has_path(graph, 3, 1);
         assert_eq!(result, false);
     }
+
+    #[test]
+    fn test_find_path() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(2, 4);
+        graph.add_directed_edge(3, 5);
+        // 0 ---> 1
+        // |      |
+        // v      v
+        // 2      3
+        // |      |
+        // v      v
+        // 4      5
+        let result = find_path(&graph, 0, 5);
+        assert_eq!(result, Some(vec![0, 1, 3, 5]));
+        let result = find_path(&graph, 2, 2);
+        assert_eq!(result, Some(vec![2]));
+        let result = find_path(&graph, 2, 5);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_dangling_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_directed_edge(0, 1);
+        // 0 ---> 1
+        // 1 never got its own entry
+        let result = has_path(graph.clone(), 0, 2);
+        assert_eq!(result, false);
+        let result = find_path(&graph, 0, 2);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_bidirectional_find_path() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 0);
+        graph.add_directed_edge(0, 4);
+        graph.add_directed_edge(5, 4);
+        // 0 ---> 1
+        // ^      |
+        // |      v
+        // 3 <--- 2
+        //
+        // 0 ---> 4 <--- 5
+        for start in 0..6 {
+            for dest in 0..6 {
+                let expected = has_path(graph.clone(), start, dest);
+                let result = bidirectional_has_path(&graph, start, dest);
+                assert_eq!(result, expected);
+                let Some(path) = bidirectional_find_path(&graph, start, dest)
+                else {
+                    continue;
+                };
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&dest));
+                for edge in path.windows(2) {
+                    assert!(graph.neighbours(&edge[0]).contains(&edge[1]));
+                }
+            }
+        }
+        let result = bidirectional_find_path(&graph, 1, 4);
+        assert_eq!(result, Some(vec![1, 2, 3, 0, 4]));
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(3, 1);
+        graph.add_directed_edge(2, 4);
+        graph.add_node(5);
+        // 0 ---> 1 <--> 3
+        // |
+        // v
+        // 2 ---> 4    5
+        let closure = transitive_closure(&graph);
+        assert_eq!(closure.nodes(), &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(closure.reachable_from(0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(closure.reachable_from(3), vec![1, 3]);
+        assert_eq!(closure.reachable_from(4), vec![4]);
+        assert_eq!(closure.reachable_from(9), vec![]);
+        assert!(closure.reachable(5, 5));
+        assert!(!closure.reachable(5, 0));
+        assert!(!closure.reachable(0, 9));
+        for u in 0..7 {
+            for v in 0..7 {
+                assert_eq!(
+                    closure.reachable(u, v),
+                    has_path(graph.clone(), u, v)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(0, 3);
+        graph.add_directed_edge(1, 3);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 4);
+        graph.add_directed_edge(0, 4);
+        // 0 ---> 1
+        // | \    |
+        // |  \   v
+        // |   -> 3 ---> 4
+        // v      ^
+        // 2 =====
+        // (2 --> 3 twice, and a shortcut 0 --> 4)
+        let reduction = transitive_reduction(&graph).unwrap();
+        assert_eq!(reduction.neighbours(&0), &[1, 2]);
+        assert_eq!(reduction.neighbours(&1), &[3]);
+        assert_eq!(reduction.neighbours(&2), &[3]);
+        assert_eq!(reduction.neighbours(&3), &[4]);
+        assert_eq!(reduction.neighbours(&4), &[]);
+        let (before, after) =
+            (transitive_closure(&graph), transitive_closure(&reduction));
+        for u in 0..5 {
+            assert_eq!(before.reachable_from(u), after.reachable_from(u));
+        }
+
+        graph.add_directed_edge(4, 2);
+        assert!(transitive_reduction(&graph).is_none());
+        graph = Graph::new();
+        graph.add_directed_edge(7, 7);
+        assert!(transitive_reduction(&graph).is_none());
+    }
+
+    #[test]
+    fn test_transitive_reduction_long_chain() {
+        // every edge i --> j with i < j, more than one `u64`
+        // per row
+        let mut graph = Graph::new();
+        for u in 0..100 {
+            for v in u + 1..100 {
+                graph.add_directed_edge(u, v);
+            }
+        }
+        let closure = transitive_closure(&graph);
+        assert!(closure.reachable(3, 99));
+        assert!(!closure.reachable(99, 3));
+        let reduction = transitive_reduction(&graph).unwrap();
+        for u in 0..99 {
+            assert_eq!(reduction.neighbours(&u), &[u + 1]);
+        }
+        assert_eq!(reduction.neighbours(&99), &[]);
+    }
 }
@@ -1,7 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    usize,
-};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub type NodeId = usize;
 
@@ -11,23 +8,36 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self { adjacency_list: HashMap::new() }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    // A node that only ever shows up at the end of
+    // a directed edge has no entry of its own, treat
+    // it as a node without neighbours
+    fn neighbours(&self, node: &NodeId) -> &[NodeId] {
+        self.adjacency_list.get(node).map_or(&[], |n| n.as_slice())
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -37,7 +47,7 @@ impl Graph {
 /// indicating whether or not there exists a directed path between the source
 /// and destination nodes.
 /// Depth-first search algorithm
-fn has_undirected_path(
+pub fn has_undirected_path(
     graph: Graph,
     start_node: NodeId,
     dest_node: NodeId,
@@ -56,7 +66,7 @@ fn has_path_helper(
         return true;
     }
     visited.insert(current_node);
-    for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
+    for neighbour in graph.neighbours(&current_node) {
         if !visited.contains(neighbour)
             && has_path_helper(graph, *neighbour, dest_node, visited)
         {
@@ -66,7 +76,51 @@ fn has_path_helper(
     false
 }
 
+/// Returns the nodes of a path from `start_node` to
+/// `dest_node` (both included), or `None` if there is no such
+/// path.
+///
+/// Unlike the depth-first search of `has_undirected_path`
+/// this is a breadth-first search: for every node we remember
+/// the node we reached it from (its predecessor), so the path
+/// found is also one with the fewest edges. It is the same
+/// search as `shortest_path::shortest_path_to`, repeated here
+/// so that the crate doesn't depend on that one.
+pub fn find_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<Vec<NodeId>> {
+    let mut predecessor: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut visited = HashSet::new();
+    visited.insert(start_node);
+    let mut queue = VecDeque::new();
+    queue.push_back(start_node);
+
+    while let Some(current_node) = queue.pop_front() {
+        if current_node == dest_node {
+            let mut path = vec![current_node];
+            let mut node = current_node;
+            while let Some(previous) = predecessor.get(&node) {
+                path.push(*previous);
+                node = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for neighbour in graph.neighbours(&current_node) {
+            if visited.insert(*neighbour) {
+                predecessor.insert(*neighbour, current_node);
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+    None
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -171,4 +225,37 @@ mod tests {
         let result = has_undirected_path(graph, 3, 7);
         assert_eq!(result, false);
     }
+
+    #[test]
+    fn test_find_path() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(4, 5);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      |
+        // 2 <----
+        // ^
+        // |
+        // v
+        // 3
+        //
+        // 4 <--> 5
+        let result = find_path(&graph, 3, 1);
+        assert_eq!(result, Some(vec![3, 2, 1]));
+        let result = find_path(&graph, 5, 4);
+        assert_eq!(result, Some(vec![5, 4]));
+        let result = find_path(&graph, 0, 4);
+        assert_eq!(result, None);
+    }
 }
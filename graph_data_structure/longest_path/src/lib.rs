@@ -1,4 +1,4 @@
-use std::{collections::HashMap, usize};
+use std::collections::HashMap;
 
 pub type NodeId = usize;
 
@@ -8,32 +8,61 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self { adjacency_list: HashMap::new() }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+
+    // A node that only ever shows up at the end of
+    // a directed edge has no entry of its own, treat
+    // it as a node without neighbours
+    fn neighbours(&self, node: &NodeId) -> &[NodeId] {
+        self.adjacency_list.get(node).map_or(&[], |n| n.as_slice())
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
+
+/// The nodes of a cycle, in the order the edges run, the
+/// last node having an edge back to the first one. A graph
+/// with a cycle has no longest path: going round once more
+/// always makes a longer one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<NodeId>);
+
 /// This `fn` takes in an a directed acyclic graph. The
 /// function should return the length of the longest path
 /// within the graph. A path may start and end at any two
 /// nodes. The length of a path is considered the number of
 /// edges in the path, not the number of nodes.
 /// Depth-first search algorithm
-fn longest_path(graph: Graph) -> u32 {
+///
+/// Returns the `Cycle` found instead if the graph isn't
+/// acyclic.
+pub fn longest_path(graph: Graph) -> Result<u32, Cycle> {
+    // `traverse_distance` would go round a cycle forever
+    if let Some(cycle) = has_cycle::find_cycle(&graph.adjacency_list) {
+        return Err(Cycle(cycle));
+    }
+
     // The `key` here is the node
     // and `value` is the distance of the node
     // from a terminal node (i.e. a node without any
@@ -59,7 +88,7 @@ fn longest_path(graph: Graph) -> u32 {
     // return the max value from the list
     // of values in distance since we want
     // the longest path
-    *distance.values().max().unwrap_or(&0)
+    Ok(*distance.values().max().unwrap_or(&0))
 }
 
 fn traverse_distance(
@@ -77,8 +106,16 @@ fn traverse_distance(
         return *distance.get(&current_node).unwrap();
     }
 
+    let neighbours = graph.neighbours(&current_node);
+    // Such a node is a terminal node too, but it wasn't
+    // found by the loop over `adjacency_list` keys
+    if neighbours.is_empty() {
+        distance.insert(current_node, 0);
+        return 0;
+    }
+
     let mut max_distance = 0;
-    for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
+    for neighbour in neighbours {
         // lets call the function recursively for each neighbour
         // of this `current_node`
         let neighbour_distance =
@@ -154,7 +191,7 @@ mod tests {
         // v
         // 3
         let distance = longest_path(graph);
-        assert_eq!(distance, 3);
+        assert_eq!(distance, Ok(3));
     }
 
     #[test]
@@ -179,7 +216,7 @@ mod tests {
         // v      v
         // 4      5
         let distance = longest_path(graph);
-        assert_eq!(distance, 3);
+        assert_eq!(distance, Ok(3));
     }
 
     #[test]
@@ -217,6 +254,39 @@ mod tests {
         //        v    |
         //        14 <-
         let distance = longest_path(graph);
-        assert_eq!(distance, 4);
+        assert_eq!(distance, Ok(4));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 1);
+        // 0 ---> 1 ---> 2
+        //        ^      |
+        //        |      v
+        //         ----- 3
+        assert_eq!(longest_path(graph), Err(Cycle(vec![1, 2, 3])));
+
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_directed_edge(0, 0);
+        assert_eq!(longest_path(graph), Err(Cycle(vec![0])));
+    }
+    #[test]
+    fn test_dangling_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        // 0 ---> 1 ---> 2, without `add_node(2)`
+        assert_eq!(longest_path(graph), Ok(2));
     }
 }
@@ -0,0 +1,420 @@
+use std::collections::{BTreeSet, HashMap};
+
+use breadth_first_search::{breadth_first_search_by, BfsTree, Graph};
+
+pub use breadth_first_search::NodeId;
+
+pub type Capacity = u64;
+
+/// A directed graph where every edge has a capacity
+#[derive(Clone, Debug, Default)]
+pub struct FlowNetwork {
+    nodes: BTreeSet<NodeId>,
+    // every edge u --> v also gets v --> u here so that flow
+    // can be pushed back along it
+    graph: Graph,
+    // summed over parallel edges
+    capacity: HashMap<(NodeId, NodeId), Capacity>,
+    edges: Vec<(NodeId, NodeId, Capacity)>,
+}
+
+impl FlowNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.nodes.insert(node_id);
+        self.graph.add_node(node_id);
+    }
+
+    // Use this method to add directed edge u --(capacity)--> v
+    pub fn add_edge(&mut self, u: NodeId, v: NodeId, capacity: Capacity) {
+        self.add_node(u);
+        self.add_node(v);
+        self.edges.push((u, v, capacity));
+        let known = self.capacity.contains_key(&(u, v))
+            || self.capacity.contains_key(&(v, u));
+        if u != v && !known {
+            self.graph.add_directed_edge(u, v);
+            self.graph.add_directed_edge(v, u);
+        }
+        *self.capacity.entry((u, v)).or_default() += capacity;
+    }
+
+    /// All the nodes of the network in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        self.nodes.iter().copied().collect()
+    }
+
+    /// The edges in the order they were added, as
+    /// `(u, v, capacity)`
+    pub fn edges(&self) -> &[(NodeId, NodeId, Capacity)] {
+        &self.edges
+    }
+}
+
+/// A maximum flow together with the minimum cut that proves
+/// it can't get any bigger
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaxFlow {
+    pub value: Capacity,
+    /// The flow along every edge of the network, as
+    /// `(u, v, flow)` in the order the edges were added
+    pub flows: Vec<(NodeId, NodeId, Capacity)>,
+    /// The nodes the source can still reach through edges
+    /// with capacity left, sorted. Everything else, sink
+    /// included, is on the sink side.
+    pub source_side: Vec<NodeId>,
+    pub sink_side: Vec<NodeId>,
+    /// The edges from the source side to the sink side, all
+    /// of them full. Their capacities add up to `value`.
+    pub cut: Vec<(NodeId, NodeId, Capacity)>,
+}
+
+/// Capacity left on every edge, going backwards along an
+/// edge undoes flow sent forwards along it
+type Residual = HashMap<(NodeId, NodeId), Capacity>;
+
+fn residual_capacity(residual: &Residual, u: NodeId, v: NodeId) -> Capacity {
+    residual.get(&(u, v)).copied().unwrap_or(0)
+}
+
+fn augment(residual: &mut Residual, u: NodeId, v: NodeId, amount: Capacity) {
+    *residual.get_mut(&(u, v)).unwrap() -= amount;
+    *residual.entry((v, u)).or_default() += amount;
+}
+
+/// A BFS that only follows edges with capacity left
+fn residual_bfs(
+    network: &FlowNetwork,
+    residual: &Residual,
+    source: NodeId,
+) -> BfsTree {
+    breadth_first_search_by(source, |node| {
+        network
+            .graph
+            .neighbours(node)
+            .iter()
+            .copied()
+            .filter(move |next| residual_capacity(residual, node, *next) > 0)
+    })
+}
+
+/// Edmonds-Karp algorithm.
+///
+/// Ford-Fulkerson with the augmenting paths found by BFS:
+/// keep pushing as much as fits along the shortest path from
+/// source to sink that still has capacity left, until there
+/// is no such path. Shortest paths keep the number of rounds
+/// down to O(V * E), so the whole thing is O(V * E^2).
+///
+/// Returns `None` if `source` and `sink` are the same node.
+pub fn edmonds_karp(
+    network: &FlowNetwork,
+    source: NodeId,
+    sink: NodeId,
+) -> Option<MaxFlow> {
+    if source == sink {
+        return None;
+    }
+
+    let mut residual = network.capacity.clone();
+    let mut value = 0;
+    while let Some(path) =
+        residual_bfs(network, &residual, source).path_to(sink)
+    {
+        let bottleneck = path
+            .windows(2)
+            .map(|edge| residual_capacity(&residual, edge[0], edge[1]))
+            .min()
+            .unwrap();
+        for edge in path.windows(2) {
+            augment(&mut residual, edge[0], edge[1], bottleneck);
+        }
+        value += bottleneck;
+    }
+
+    Some(max_flow(network, &residual, source, value))
+}
+
+/// Dinic's algorithm.
+///
+/// Every round a BFS sorts the nodes into levels by their
+/// distance from the source, then DFS pushes flow only
+/// along edges going one level down until the sink is cut
+/// off from the source (a blocking flow). Each node remembers
+/// which of its edges it has given up on, so no edge is
+/// tried twice in a round. The distance to the sink grows
+/// every round, giving O(V^2 * E) overall and much better in
+/// practice.
+///
+/// Returns `None` if `source` and `sink` are the same node.
+pub fn dinic(
+    network: &FlowNetwork,
+    source: NodeId,
+    sink: NodeId,
+) -> Option<MaxFlow> {
+    if source == sink {
+        return None;
+    }
+
+    let mut residual = network.capacity.clone();
+    let mut value = 0;
+    loop {
+        let level = residual_bfs(network, &residual, source).distance;
+        if !level.contains_key(&sink) {
+            break;
+        }
+
+        let mut blocking = BlockingFlow {
+            network,
+            residual: &mut residual,
+            level: &level,
+            next_edge: HashMap::new(),
+            sink,
+        };
+        loop {
+            let pushed = blocking.push(source, Capacity::MAX);
+            if pushed == 0 {
+                break;
+            }
+            value += pushed;
+        }
+    }
+
+    Some(max_flow(network, &residual, source, value))
+}
+
+struct BlockingFlow<'a> {
+    network: &'a FlowNetwork,
+    residual: &'a mut Residual,
+    level: &'a HashMap<NodeId, usize>,
+    // index of the first edge of each node still worth trying
+    next_edge: HashMap<NodeId, usize>,
+    sink: NodeId,
+}
+
+impl BlockingFlow<'_> {
+    /// Pushes up to `limit` along a single path from
+    /// `current_node` down to the sink, returns how much
+    /// made it
+    fn push(&mut self, current_node: NodeId, limit: Capacity) -> Capacity {
+        if current_node == self.sink {
+            return limit;
+        }
+
+        let neighbours = self.network.graph.neighbours(current_node);
+        loop {
+            let i = *self.next_edge.entry(current_node).or_default();
+            let Some(neighbour) = neighbours.get(i).copied() else {
+                return 0;
+            };
+
+            let left =
+                residual_capacity(self.residual, current_node, neighbour);
+            let one_level_down = self.level.get(&neighbour)
+                == Some(&(self.level[&current_node] + 1));
+            if left > 0 && one_level_down {
+                let pushed = self.push(neighbour, limit.min(left));
+                if pushed > 0 {
+                    augment(self.residual, current_node, neighbour, pushed);
+                    return pushed;
+                }
+            }
+            self.next_edge.insert(current_node, i + 1);
+        }
+    }
+}
+
+fn max_flow(
+    network: &FlowNetwork,
+    residual: &Residual,
+    source: NodeId,
+    value: Capacity,
+) -> MaxFlow {
+    // net flow u --> v for every pair of nodes, shared out
+    // over the parallel edges in the order they were added
+    let mut net_flow = HashMap::new();
+    let mut flows = Vec::new();
+    for (u, v, capacity) in &network.edges {
+        let left = net_flow.entry((*u, *v)).or_insert_with(|| {
+            network.capacity[&(*u, *v)]
+                .saturating_sub(residual_capacity(residual, *u, *v))
+        });
+        let flow = (*capacity).min(*left);
+        *left -= flow;
+        flows.push((*u, *v, flow));
+    }
+
+    let mut source_side = residual_bfs(network, residual, source).order;
+    source_side.sort_unstable();
+    let sink_side: Vec<NodeId> = network
+        .nodes()
+        .into_iter()
+        .filter(|node| source_side.binary_search(node).is_err())
+        .collect();
+    let cut = network
+        .edges
+        .iter()
+        .filter(|(u, v, _)| {
+            source_side.binary_search(u).is_ok()
+                && source_side.binary_search(v).is_err()
+        })
+        .copied()
+        .collect();
+
+    MaxFlow { value, flows, source_side, sink_side, cut }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::Rng;
+
+    /// Every edge within its capacity, what flows into a node
+    /// flows out of it, and the cut is as big as the flow
+    fn assert_valid(
+        network: &FlowNetwork,
+        flow: &MaxFlow,
+        source: NodeId,
+        sink: NodeId,
+    ) {
+        let mut balance: HashMap<NodeId, i64> = HashMap::new();
+        for ((u, v, capacity), (_, _, f)) in
+            network.edges().iter().zip(&flow.flows)
+        {
+            assert!(f <= capacity);
+            *balance.entry(*u).or_default() -= *f as i64;
+            *balance.entry(*v).or_default() += *f as i64;
+        }
+        for node in network.nodes() {
+            let expected = match node {
+                n if n == source => -(flow.value as i64),
+                n if n == sink => flow.value as i64,
+                _ => 0,
+            };
+            assert_eq!(balance.get(&node).copied().unwrap_or(0), expected);
+        }
+
+        let cut: Capacity = flow.cut.iter().map(|(_, _, c)| c).sum();
+        assert_eq!(cut, flow.value);
+        assert!(flow.source_side.contains(&source));
+        assert!(flow.sink_side.contains(&sink));
+    }
+
+    fn example_network() -> FlowNetwork {
+        let mut network = FlowNetwork::new();
+        network.add_edge(0, 1, 16);
+        network.add_edge(0, 2, 13);
+        network.add_edge(1, 3, 12);
+        network.add_edge(2, 1, 4);
+        network.add_edge(2, 4, 14);
+        network.add_edge(3, 2, 9);
+        network.add_edge(3, 5, 20);
+        network.add_edge(4, 3, 7);
+        network.add_edge(4, 5, 4);
+        //        --(16)-> 1 --(12)-> 3 --(20)-
+        //       |         ^       |  ^        |
+        //       |         |       |  |        v
+        //       0        (4)    (9) (7)       5
+        //       |         |       |  |        ^
+        //       |         |       v  |        |
+        //        --(13)-> 2 --(14)-> 4 --(4)--
+        network
+    }
+
+    #[test]
+    fn test_max_flow_00() {
+        let network = example_network();
+        for flow in [
+            edmonds_karp(&network, 0, 5).unwrap(),
+            dinic(&network, 0, 5).unwrap(),
+        ] {
+            assert_valid(&network, &flow, 0, 5);
+            assert_eq!(flow.value, 23);
+            assert_eq!(flow.source_side, vec![0, 1, 2, 4]);
+            assert_eq!(flow.sink_side, vec![3, 5]);
+            assert_eq!(flow.cut, vec![(1, 3, 12), (4, 3, 7), (4, 5, 4)]);
+        }
+    }
+
+    #[test]
+    fn test_max_flow_01() {
+        let mut network = FlowNetwork::new();
+        network.add_edge(0, 1, 3);
+        network.add_edge(0, 1, 2);
+        network.add_edge(1, 2, 4);
+        network.add_edge(2, 1, 10);
+        network.add_edge(1, 1, 7);
+        network.add_node(3);
+        // 0 ==(3, 2)==> 1 <--(10)-- 2
+        //               |           ^
+        //                ----(4)----
+        let flow = dinic(&network, 0, 2).unwrap();
+        assert_valid(&network, &flow, 0, 2);
+        assert_eq!(flow.value, 4);
+        assert_eq!(
+            flow.flows,
+            vec![(0, 1, 3), (0, 1, 1), (1, 2, 4), (2, 1, 0), (1, 1, 0)]
+        );
+        assert_eq!(flow.sink_side, vec![2, 3]);
+        assert_eq!(edmonds_karp(&network, 0, 2).unwrap().flows, flow.flows);
+
+        // 3 can't be reached at all
+        let flow = edmonds_karp(&network, 0, 3).unwrap();
+        assert_eq!(flow.value, 0);
+        assert!(flow.cut.is_empty());
+        assert_eq!(dinic(&network, 2, 2), None);
+    }
+
+    #[test]
+    fn test_undoing_flow() {
+        let mut network = FlowNetwork::new();
+        network.add_edge(0, 1, 1);
+        network.add_edge(0, 2, 1);
+        network.add_edge(1, 2, 1);
+        network.add_edge(1, 3, 1);
+        network.add_edge(2, 3, 1);
+        // 0 ---> 1 ---> 3
+        // |      |      ^
+        // v      |      |
+        // 2 <----       |
+        // |             |
+        //  -------------
+        // the path 0 -> 1 -> 2 -> 3 blocks both other paths
+        // until its middle edge is pushed back
+        for flow in [
+            edmonds_karp(&network, 0, 3).unwrap(),
+            dinic(&network, 0, 3).unwrap(),
+        ] {
+            assert_valid(&network, &flow, 0, 3);
+            assert_eq!(flow.value, 2);
+            assert_eq!(flow.flows[2], (1, 2, 0));
+        }
+    }
+
+    #[test]
+    fn test_same_value() {
+        let mut rng = Rng::new(2024);
+        for _ in 0..20 {
+            let nodes = 2 + rng.below(30);
+            let mut network = FlowNetwork::new();
+            for _ in 0..rng.below(150) {
+                let (u, v) = (rng.below(nodes), rng.below(nodes));
+                network.add_edge(u, v, rng.below(20) as Capacity);
+            }
+            network.add_node(0);
+            network.add_node(1);
+
+            let a = edmonds_karp(&network, 0, 1).unwrap();
+            let b = dinic(&network, 0, 1).unwrap();
+            assert_valid(&network, &a, 0, 1);
+            assert_valid(&network, &b, 0, 1);
+            assert_eq!(a.value, b.value);
+            // the source side of a minimum cut found this way
+            // is the same no matter which maximum flow we got
+            assert_eq!(a.source_side, b.source_side);
+        }
+    }
+}
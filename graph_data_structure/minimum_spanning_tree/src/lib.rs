@@ -0,0 +1,261 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use union_find::DisjointSet;
+
+pub type NodeId = usize;
+pub type Weight = i64;
+
+/// An undirected weighted graph
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<(NodeId, Weight)>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // add a bi-directional edge u <--(w)--> v
+    pub fn add_weighted_undirected_edge(
+        &mut self,
+        u: NodeId,
+        v: NodeId,
+        weight: Weight,
+    ) {
+        self.adjacency_list.entry(u).or_default().push((v, weight));
+        self.adjacency_list.entry(v).or_default().push((u, weight));
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    /// Every edge once, as `(u, v, weight)` with `u < v`.
+    /// Self-loops are left out, they never end up in a
+    /// spanning tree.
+    pub fn edges(&self) -> Vec<(NodeId, NodeId, Weight)> {
+        let mut edges = Vec::new();
+        for (u, neighbours) in &self.adjacency_list {
+            for (v, weight) in neighbours {
+                if u < v {
+                    edges.push((*u, *v, *weight));
+                }
+            }
+        }
+        edges
+    }
+}
+
+/// A minimum spanning forest: one minimum spanning tree per
+/// connected component of the graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpanningForest {
+    /// The chosen edges as `(u, v, weight)` with `u < v`
+    pub edges: Vec<(NodeId, NodeId, Weight)>,
+    pub total_weight: Weight,
+    /// Number of trees, i.e. connected components
+    pub trees: usize,
+}
+
+impl SpanningForest {
+    /// A single tree spans the whole graph
+    pub fn is_tree(&self) -> bool {
+        self.trees <= 1
+    }
+}
+
+/// Kruskal's algorithm.
+///
+/// Go through the edges from the lightest to the heaviest
+/// and keep every edge that joins two different trees. A
+/// union-find tells us in (almost) constant time whether
+/// both ends are already in the same tree.
+pub fn kruskal(graph: &Graph) -> SpanningForest {
+    let mut edges = graph.edges();
+    edges.sort_unstable_by_key(|(u, v, weight)| (*weight, *u, *v));
+
+    let mut trees = DisjointSet::new();
+    for node in graph.adjacency_list.keys() {
+        trees.make_set(*node);
+    }
+
+    let mut chosen = Vec::new();
+    let mut total_weight = 0;
+    for (u, v, weight) in edges {
+        if trees.union(u, v) {
+            chosen.push((u, v, weight));
+            total_weight += weight;
+        }
+    }
+
+    SpanningForest { edges: chosen, total_weight, trees: trees.count() }
+}
+
+/// Prim's algorithm.
+///
+/// Grow a tree from a start node, always adding the lightest
+/// edge leaving the tree. A min-heap holds the edges leaving
+/// the tree, the ones leading back into it are skipped as
+/// they come out. Once a tree can't grow any further we
+/// start the next one from a node not yet covered.
+pub fn prim(graph: &Graph) -> SpanningForest {
+    let mut in_tree = HashSet::new();
+    let mut chosen = Vec::new();
+    let mut total_weight = 0;
+    let mut trees = 0;
+
+    for start in graph.nodes() {
+        if in_tree.contains(&start) {
+            continue;
+        }
+        trees += 1;
+        in_tree.insert(start);
+
+        let mut heap = BinaryHeap::new();
+        for (neighbour, weight) in &graph.adjacency_list[&start] {
+            heap.push(Reverse((*weight, start, *neighbour)));
+        }
+
+        while let Some(Reverse((weight, from, to))) = heap.pop() {
+            if !in_tree.insert(to) {
+                continue;
+            }
+            chosen.push((from.min(to), from.max(to), weight));
+            total_weight += weight;
+            for (neighbour, weight) in &graph.adjacency_list[&to] {
+                if !in_tree.contains(neighbour) {
+                    heap.push(Reverse((*weight, to, *neighbour)));
+                }
+            }
+        }
+    }
+
+    SpanningForest { edges: chosen, total_weight, trees }
+}
+
+/// The minimum spanning tree of a connected graph, `None`
+/// if the graph isn't connected (see `kruskal` or `prim` for
+/// a spanning forest).
+pub fn minimum_spanning_tree(graph: &Graph) -> Option<SpanningForest> {
+    let forest = kruskal(graph);
+    if forest.is_tree() {
+        Some(forest)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(
+        mut edges: Vec<(NodeId, NodeId, Weight)>,
+    ) -> Vec<(NodeId, NodeId, Weight)> {
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn test_add_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_weighted_undirected_edge(1, 2, 5);
+        graph.add_weighted_undirected_edge(2, 2, 1);
+        assert_eq!(graph.nodes(), vec![0, 1, 2]);
+        assert_eq!(graph.edges(), vec![(1, 2, 5)]);
+    }
+
+    #[test]
+    fn test_spanning_tree_00() {
+        let mut graph = Graph::new();
+        graph.add_weighted_undirected_edge(0, 1, 4);
+        graph.add_weighted_undirected_edge(0, 7, 8);
+        graph.add_weighted_undirected_edge(1, 2, 8);
+        graph.add_weighted_undirected_edge(1, 7, 11);
+        graph.add_weighted_undirected_edge(2, 3, 7);
+        graph.add_weighted_undirected_edge(2, 8, 2);
+        graph.add_weighted_undirected_edge(2, 5, 4);
+        graph.add_weighted_undirected_edge(3, 4, 9);
+        graph.add_weighted_undirected_edge(3, 5, 14);
+        graph.add_weighted_undirected_edge(4, 5, 10);
+        graph.add_weighted_undirected_edge(5, 6, 2);
+        graph.add_weighted_undirected_edge(6, 7, 1);
+        graph.add_weighted_undirected_edge(6, 8, 6);
+        graph.add_weighted_undirected_edge(7, 8, 7);
+
+        let kruskal = kruskal(&graph);
+        let prim = prim(&graph);
+        assert_eq!(kruskal.total_weight, 37);
+        assert_eq!(prim.total_weight, 37);
+        assert_eq!(kruskal.edges.len(), 8);
+        assert_eq!(prim.edges.len(), 8);
+        assert!(kruskal.is_tree());
+        assert!(prim.is_tree());
+        assert_eq!(minimum_spanning_tree(&graph), Some(kruskal));
+    }
+
+    #[test]
+    fn test_spanning_tree_01() {
+        let mut graph = Graph::new();
+        graph.add_weighted_undirected_edge(0, 1, 1);
+        graph.add_weighted_undirected_edge(1, 2, 2);
+        graph.add_weighted_undirected_edge(0, 2, 3);
+        graph.add_weighted_undirected_edge(2, 3, -1);
+        // a heavier parallel edge
+        graph.add_weighted_undirected_edge(3, 2, 5);
+        // 0 <-(1)-> 1 <-(2)-> 2 <-(-1)-> 3
+        // ^                   ^
+        // |                   |
+        //  --------(3)--------
+        let expected = vec![(0, 1, 1), (1, 2, 2), (2, 3, -1)];
+        assert_eq!(sorted(kruskal(&graph).edges), expected);
+        assert_eq!(sorted(prim(&graph).edges), expected);
+        assert_eq!(kruskal(&graph).total_weight, 2);
+    }
+
+    #[test]
+    fn test_spanning_forest() {
+        let mut graph = Graph::new();
+        graph.add_weighted_undirected_edge(0, 1, 3);
+        graph.add_weighted_undirected_edge(1, 2, 1);
+        graph.add_weighted_undirected_edge(0, 2, 2);
+        graph.add_weighted_undirected_edge(3, 4, 7);
+        graph.add_node(5);
+        // 0 <-(3)-> 1 <-(1)-> 2    3 <-(7)-> 4    5
+        // ^                   ^
+        // |                   |
+        //  --------(2)--------
+        for forest in [kruskal(&graph), prim(&graph)] {
+            assert_eq!(forest.trees, 3);
+            assert!(!forest.is_tree());
+            assert_eq!(forest.total_weight, 10);
+            assert_eq!(
+                sorted(forest.edges),
+                vec![(0, 2, 2), (1, 2, 1), (3, 4, 7)]
+            );
+        }
+        assert_eq!(minimum_spanning_tree(&graph), None);
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = Graph::new();
+        let forest = kruskal(&graph);
+        assert_eq!(forest.trees, 0);
+        assert!(forest.edges.is_empty());
+        assert_eq!(prim(&graph), forest);
+    }
+}
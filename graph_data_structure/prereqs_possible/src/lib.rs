@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
 
 /// This representation is vastly simpler
 /// than the representation used elsewhere in
@@ -15,19 +14,9 @@ type Graph = HashMap<u32, Vec<u32>>;
 /// must be taken before course B. The function
 /// should return a boolean indicating whether or
 /// not it is possible to complete all courses.
-fn prereqs_possible(num_courses: u32, prereqs: &[[u32; 2]]) -> bool {
-    let mut graph = build_graph(num_courses, prereqs);
-
-    let mut visited: HashSet<u32> = HashSet::new();
-    let mut visiting: HashSet<u32> = HashSet::new();
-
-    for node in graph.keys() {
-        if detect_cycle(node, &graph, &mut visited, &mut visiting) {
-            return false;
-        }
-    }
-
-    true
+pub fn prereqs_possible(num_courses: u32, prereqs: &[[u32; 2]]) -> bool {
+    let graph = build_graph(num_courses, prereqs);
+    has_cycle::find_cycle(&graph).is_none()
 }
 
 fn build_graph(num_courses: u32, prereqs: &[[u32; 2]]) -> Graph {
@@ -39,34 +28,13 @@ fn build_graph(num_courses: u32, prereqs: &[[u32; 2]]) -> Graph {
     for prereq in prereqs {
         let course_a = prereq[0];
         let course_b = prereq[1];
-        graph.entry(course_a).or_insert(Vec::new()).push(course_b);
+        graph.entry(course_a).or_default().push(course_b);
     }
     graph
 }
 
-fn detect_cycle(
-    node: &u32,
-    graph: &Graph,
-    visited: &mut HashSet<u32>,
-    visiting: &mut HashSet<u32>,
-) -> bool {
-    if visiting.contains(node) {
-        return true;
-    }
-
-    visiting.insert(*node);
-
-    for neighbor in graph.get(node).unwrap() {
-        if detect_cycle(neighbor, graph, visited, visiting) {
-            return true;
-        }
-    }
-    visited.insert(*node);
-    visiting.remove(node);
-    false
-}
-
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
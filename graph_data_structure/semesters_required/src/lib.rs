@@ -1,4 +1,4 @@
-use std::{collections::HashMap, usize};
+use std::{cmp::Reverse, collections::HashMap, error::Error, fmt};
 
 pub type NodeId = usize;
 
@@ -8,23 +8,29 @@ pub struct Graph {
 }
 
 impl Graph {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self { adjacency_list: HashMap::new() }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -44,7 +50,10 @@ impl Graph {
 ///
 /// You can assume that it is possible to eventually
 /// complete all courses.
-fn semesters_required(num_courses: usize, prereqs: &[[usize; 2]]) -> usize {
+pub fn semesters_required(
+    num_courses: usize,
+    prereqs: &[[usize; 2]],
+) -> usize {
     let graph = build_graph(num_courses, prereqs);
 
     // This is a variation of the longest
@@ -143,6 +152,143 @@ fn traverse_distance(
     *distance.get(&current_node).unwrap()
 }
 
+/// Limits for `plan_semesters`, everything is unlimited
+/// by default.
+#[derive(Clone, Debug, Default)]
+pub struct PlanOptions {
+    /// The most courses that can be taken in one semester
+    pub max_courses: Option<usize>,
+    /// The most credits that can be taken in one semester
+    pub max_credits: Option<u32>,
+    /// Credits of each course indexed by course id, any
+    /// course without an entry is worth 1 credit
+    pub credits: Vec<u32>,
+}
+
+/// Why no plan could be made
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlanError {
+    /// The prerequisites go round in a circle, each course of
+    /// the cycle being a prerequisite of the next one and the
+    /// last one a prerequisite of the first
+    PrerequisiteCycle(Vec<usize>),
+    /// The course can't fit in any semester under the limits
+    CourseDoesNotFit(usize),
+    /// A prerequisite names a course that isn't one of the
+    /// `num_courses` courses
+    UnknownCourse(usize),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::PrerequisiteCycle(cycle) => {
+                write!(f, "prerequisite cycle between courses {:?}", cycle)
+            }
+            PlanError::CourseDoesNotFit(course) => {
+                write!(f, "course {} does not fit in any semester", course)
+            }
+            PlanError::UnknownCourse(course) => {
+                write!(f, "unknown course {} in the prerequisites", course)
+            }
+        }
+    }
+}
+
+impl Error for PlanError {}
+
+/// Same input as `semesters_required` but returns the
+/// courses to take in every semester, respecting the
+/// limits in `options`.
+///
+/// Each semester we look at the courses whose prerequisites
+/// have all been taken in earlier semesters and take the
+/// ones heading the longest chain of courses still to come
+/// first, as long as they fit. Without limits this gives
+/// exactly `semesters_required` semesters. With a limit on
+/// the number of courses finding the true minimum is NP-hard
+/// in general so this is a (good) heuristic.
+pub fn plan_semesters(
+    num_courses: usize,
+    prereqs: &[[usize; 2]],
+    options: &PlanOptions,
+) -> Result<Vec<Vec<usize>>, PlanError> {
+    if let Some(course) =
+        prereqs.iter().flatten().find(|course| **course >= num_courses)
+    {
+        return Err(PlanError::UnknownCourse(*course));
+    }
+    if let Err(cycle) = topological_sort::course_order(num_courses, prereqs) {
+        return Err(PlanError::PrerequisiteCycle(cycle.0));
+    }
+
+    let credits = |course: usize| *options.credits.get(course).unwrap_or(&1);
+    for course in 0..num_courses {
+        let too_many = options.max_courses == Some(0);
+        let too_heavy =
+            options.max_credits.is_some_and(|max| credits(course) > max);
+        if too_many || too_heavy {
+            return Err(PlanError::CourseDoesNotFit(course));
+        }
+    }
+
+    let graph = build_graph(num_courses, prereqs);
+
+    // number of courses in the longest chain starting
+    // with each course, the same distance `longest_path`
+    // works out
+    let mut chain_length = HashMap::new();
+    for course in 0..num_courses {
+        traverse_distance(&graph, course, &mut chain_length);
+    }
+
+    let mut missing_prereqs = vec![0; num_courses];
+    for [_, course_b] in prereqs {
+        missing_prereqs[*course_b] += 1;
+    }
+    let mut available: Vec<usize> =
+        (0..num_courses).filter(|c| missing_prereqs[*c] == 0).collect();
+
+    let mut plan = Vec::new();
+    while !available.is_empty() {
+        available.sort_by_key(|c| (Reverse(chain_length[c]), *c));
+
+        let mut semester = Vec::new();
+        let mut semester_credits = 0;
+        let mut left_over = Vec::new();
+        for course in available {
+            let fits_courses =
+                options.max_courses.is_none_or(|max| semester.len() < max);
+            let fits_credits = options
+                .max_credits
+                .is_none_or(|max| semester_credits + credits(course) <= max);
+            if fits_courses && fits_credits {
+                semester.push(course);
+                semester_credits += credits(course);
+            } else {
+                left_over.push(course);
+            }
+        }
+
+        // courses unlocked this semester can only be
+        // taken from next semester on
+        for course in &semester {
+            for next in graph.adjacency_list.get(course).unwrap() {
+                missing_prereqs[*next] -= 1;
+                if missing_prereqs[*next] == 0 {
+                    left_over.push(*next);
+                }
+            }
+        }
+
+        semester.sort_unstable();
+        plan.push(semester);
+        available = left_over;
+    }
+
+    Ok(plan)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +340,82 @@ mod tests {
         let num_semeseters = semesters_required(num_courses, &prereqs);
         assert_eq!(num_semeseters, 2);
     }
+
+    #[test]
+    fn test_plan_semesters_00() {
+        let num_courses = 6;
+        let prereqs = [[1, 2], [2, 4], [3, 5], [0, 5]];
+        let options = PlanOptions::default();
+        let plan = plan_semesters(num_courses, &prereqs, &options);
+        assert_eq!(plan, Ok(vec![vec![0, 1, 3], vec![2, 5], vec![4]]));
+    }
+
+    #[test]
+    fn test_plan_semesters_01() {
+        // without limits the plan is as long as `semesters_required`
+        let cases: [(usize, &[[usize; 2]]); 4] = [
+            (7, &[[4, 3], [3, 2], [2, 1], [1, 0], [5, 2], [5, 6]]),
+            (5, &[[1, 0], [3, 4], [1, 2], [3, 2]]),
+            (12, &[]),
+            (6, &[[3, 4], [3, 0], [3, 1], [3, 2], [3, 5]]),
+        ];
+        for (num_courses, prereqs) in cases {
+            let options = PlanOptions::default();
+            let plan = plan_semesters(num_courses, prereqs, &options).unwrap();
+            assert_eq!(plan.len(), semesters_required(num_courses, prereqs));
+        }
+    }
+
+    #[test]
+    fn test_plan_semesters_max_courses() {
+        let num_courses = 6;
+        let prereqs = [[1, 2], [2, 4], [3, 5], [0, 5]];
+        let options =
+            PlanOptions { max_courses: Some(2), ..Default::default() };
+        let plan = plan_semesters(num_courses, &prereqs, &options);
+        // 1 heads the longest chain (1 -> 2 -> 4) so goes first
+        assert_eq!(plan, Ok(vec![vec![0, 1], vec![2, 3], vec![4, 5]]));
+    }
+
+    #[test]
+    fn test_plan_semesters_max_credits() {
+        let num_courses = 4;
+        let prereqs = [[0, 3]];
+        let options = PlanOptions {
+            max_credits: Some(6),
+            credits: vec![3, 4, 2, 5],
+            ..Default::default()
+        };
+        let plan = plan_semesters(num_courses, &prereqs, &options);
+        assert_eq!(plan, Ok(vec![vec![0, 2], vec![1], vec![3]]));
+
+        let options = PlanOptions {
+            max_credits: Some(4),
+            credits: vec![3, 4, 2, 5],
+            ..Default::default()
+        };
+        let plan = plan_semesters(num_courses, &prereqs, &options);
+        assert_eq!(plan, Err(PlanError::CourseDoesNotFit(3)));
+    }
+
+    #[test]
+    fn test_plan_semesters_cycle() {
+        let num_courses = 8;
+        let prereqs = [[1, 0], [0, 6], [2, 0], [0, 5], [3, 7], [7, 4], [4, 3]];
+        let options = PlanOptions::default();
+        let plan = plan_semesters(num_courses, &prereqs, &options);
+        assert_eq!(plan, Err(PlanError::PrerequisiteCycle(vec![3, 7, 4])));
+    }
+
+    #[test]
+    fn test_plan_semesters_unknown_course() {
+        let prereqs = [[0, 1], [1, 4]];
+        let options = PlanOptions::default();
+        let plan = plan_semesters(4, &prereqs, &options);
+        assert_eq!(plan, Err(PlanError::UnknownCourse(4)));
+        assert_eq!(
+            plan.unwrap_err().to_string(),
+            "unknown course 4 in the prerequisites"
+        );
+    }
 }
@@ -0,0 +1,105 @@
+//! Compares the one-sided BFS of `shortest_path_to` with
+//! `bidirectional_shortest_path` on a large random graph, and
+//! `has_path::find_path` with `has_path::bidirectional_find_path`
+//! on the same edges taken as directed ones.
+//!
+//! Run with `cargo bench`. The graph size can be changed with
+//! the `NODES` and `EDGES_PER_NODE` environment variables.
+use std::{env, time::Instant};
+
+use graph_generators::Rng;
+use shortest_path::{bidirectional_shortest_path, shortest_path_to, Graph};
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn main() {
+    let nodes = env_or("NODES", 200_000);
+    let edges_per_node = env_or("EDGES_PER_NODE", 5);
+    let queries = env_or("QUERIES", 50);
+    let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+    let mut graph = Graph::new();
+    let mut directed = has_path::Graph::new();
+    for node in 0..nodes {
+        graph.add_node(node);
+        directed.add_node(node);
+    }
+    for node in 0..nodes {
+        for _ in 0..edges_per_node {
+            let neighbour = rng.below(nodes);
+            graph.add_undirected_edge(node, neighbour);
+            directed.add_directed_edge(node, neighbour);
+        }
+    }
+    let pairs: Vec<(usize, usize)> =
+        (0..queries).map(|_| (rng.below(nodes), rng.below(nodes))).collect();
+
+    let now = Instant::now();
+    let one_sided: Vec<_> = pairs
+        .iter()
+        .map(|(start, dest)| shortest_path_to(&graph, *start, *dest))
+        .collect();
+    let one_sided_time = now.elapsed();
+
+    let now = Instant::now();
+    let bidirectional: Vec<_> = pairs
+        .iter()
+        .map(|(start, dest)| {
+            bidirectional_shortest_path(&graph, *start, *dest)
+        })
+        .collect();
+    let bidirectional_time = now.elapsed();
+
+    for (a, b) in one_sided.iter().zip(&bidirectional) {
+        assert_eq!(a.as_ref().map(|p| p.len()), b.as_ref().map(|p| p.len()));
+    }
+
+    let now = Instant::now();
+    let one_sided: Vec<_> = pairs
+        .iter()
+        .map(|(start, dest)| has_path::find_path(&directed, *start, *dest))
+        .collect();
+    let directed_one_sided_time = now.elapsed();
+
+    let now = Instant::now();
+    let bidirectional: Vec<_> = pairs
+        .iter()
+        .map(|(start, dest)| {
+            has_path::bidirectional_find_path(&directed, *start, *dest)
+        })
+        .collect();
+    let directed_bidirectional_time = now.elapsed();
+
+    for (a, b) in one_sided.iter().zip(&bidirectional) {
+        assert_eq!(a.is_some(), b.is_some());
+    }
+
+    println!(
+        "{} nodes, {} edges, {} queries",
+        nodes,
+        nodes * edges_per_node,
+        queries
+    );
+    println!(
+        "one-sided BFS:           {:>10.2?} ({:.2?} per query)",
+        one_sided_time,
+        one_sided_time / queries as u32
+    );
+    println!(
+        "bidirectional BFS:       {:>10.2?} ({:.2?} per query)",
+        bidirectional_time,
+        bidirectional_time / queries as u32
+    );
+    println!(
+        "directed, one-sided:     {:>10.2?} ({:.2?} per query)",
+        directed_one_sided_time,
+        directed_one_sided_time / queries as u32
+    );
+    println!(
+        "directed, bidirectional: {:>10.2?} ({:.2?} per query)",
+        directed_bidirectional_time,
+        directed_bidirectional_time / queries as u32
+    );
+}
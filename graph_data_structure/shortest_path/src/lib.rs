@@ -1,33 +1,52 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    usize,
-};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub type NodeId = usize;
 
 #[derive(Clone, Debug)]
 pub struct Graph {
     adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+    // the same edges pointing the other way (v --> u for
+    // every u --> v) so that we can also search backwards
+    // from a destination node
+    reverse_adjacency_list: HashMap<NodeId, Vec<NodeId>>,
 }
 
 impl Graph {
-    fn new() -> Self {
-        Self { adjacency_list: HashMap::new() }
+    pub fn new() -> Self {
+        Self {
+            adjacency_list: HashMap::new(),
+            reverse_adjacency_list: HashMap::new(),
+        }
     }
 
-    fn add_node(&mut self, node_id: NodeId) {
-        self.adjacency_list.entry(node_id).or_insert(Vec::new());
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+        self.reverse_adjacency_list.entry(node_id).or_default();
     }
 
     // Use this method to add directed edge u --> v
-    fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.reverse_adjacency_list.entry(v).or_default().push(u);
     }
 
     // add a bi-directional edge u <--> v
-    fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
-        self.adjacency_list.entry(u).or_insert(Vec::new()).push(v);
-        self.adjacency_list.entry(v).or_insert(Vec::new()).push(u);
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.add_directed_edge(u, v);
+        self.add_directed_edge(v, u);
+    }
+
+    // A node that only ever shows up at the end of
+    // a directed edge has no entry of its own, treat
+    // it as a node without neighbours
+    fn neighbours(&self, node: &NodeId) -> &[NodeId] {
+        self.adjacency_list.get(node).map_or(&[], |n| n.as_slice())
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -36,28 +55,257 @@ impl Graph {
 /// should return the length of the shortest path between A and B.
 /// Consider the length as the number of edges in the path, not
 /// the number of nodes. If there is no path between A and B,
-/// then return -1.
+/// then return `None`.
 /// Breadth-first search algorithm is better than Depth-first here.
-fn shortest_path(graph: Graph, start_node: NodeId, dest_node: NodeId) -> i32 {
+pub fn shortest_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<usize> {
+    let path = shortest_path_to(graph, start_node, dest_node)?;
+    Some(path.len() - 1)
+}
+
+/// Same as `shortest_path` but returns the nodes on the path,
+/// `start_node` and `dest_node` included, instead of its length.
+pub fn shortest_path_to(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<Vec<NodeId>> {
+    shortest_path_to_any(graph, start_node, &[dest_node])
+}
+
+/// Returns the shortest path from `start_node` to whichever of
+/// the `dest_nodes` is the closest to it.
+///
+/// Instead of the distance we remember, for every node, the
+/// node we came from when we first reached it (the
+/// predecessor). Following the predecessors back from the
+/// destination gives us the path in reverse.
+pub fn shortest_path_to_any(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_nodes: &[NodeId],
+) -> Option<Vec<NodeId>> {
+    let mut predecessor: HashMap<NodeId, NodeId> = HashMap::new();
     let mut visited = HashSet::new();
     visited.insert(start_node);
-    let mut queue: VecDeque<(NodeId, i32)> = VecDeque::new();
-    queue.push_back((start_node, 0i32));
+    let mut queue = VecDeque::new();
+    queue.push_back(start_node);
 
-    while !queue.is_empty() {
-        let (current_node, distance) = queue.pop_front().unwrap();
-        if current_node == dest_node {
-            return distance;
+    while let Some(current_node) = queue.pop_front() {
+        if dest_nodes.contains(&current_node) {
+            let mut path = vec![current_node];
+            let mut node = current_node;
+            while let Some(previous) = predecessor.get(&node) {
+                path.push(*previous);
+                node = *previous;
+            }
+            path.reverse();
+            return Some(path);
         }
 
-        for neighbour in graph.adjacency_list.get(&current_node).unwrap() {
-            if !visited.contains(neighbour) {
-                visited.insert(*neighbour);
-                queue.push_back((*neighbour, distance + 1));
+        for neighbour in graph.neighbours(&current_node) {
+            if visited.insert(*neighbour) {
+                predecessor.insert(*neighbour, current_node);
+                queue.push_back(*neighbour);
             }
         }
     }
-    return -1;
+    None
+}
+
+/// Returns every shortest path between `start_node` and
+/// `dest_node`, or an empty list if there is no path.
+///
+/// This time a node can have several predecessors: every
+/// node one level closer to the start with an edge to it.
+/// Once the BFS has reached the level of `dest_node` we
+/// walk all of the predecessors back to `start_node`.
+pub fn all_shortest_paths(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Vec<Vec<NodeId>> {
+    let mut distance: HashMap<NodeId, usize> = HashMap::new();
+    let mut predecessors: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    distance.insert(start_node, 0);
+    let mut queue = VecDeque::new();
+    queue.push_back(start_node);
+
+    while let Some(current_node) = queue.pop_front() {
+        let current_distance = distance[&current_node];
+        if distance.get(&dest_node) == Some(&current_distance) {
+            // every node from here on is at least as far
+            // away as `dest_node` so it can't be on a
+            // shortest path to it
+            break;
+        }
+
+        for neighbour in graph.neighbours(&current_node) {
+            match distance.get(neighbour) {
+                None => {
+                    distance.insert(*neighbour, current_distance + 1);
+                    predecessors.insert(*neighbour, vec![current_node]);
+                    queue.push_back(*neighbour);
+                }
+                Some(d) if *d == current_distance + 1 => {
+                    // a parallel edge, `current_node` is already
+                    // in there. Its edges are all seen in one go
+                    // so it would be the last one.
+                    let previous = predecessors.entry(*neighbour).or_default();
+                    if previous.last() != Some(&current_node) {
+                        previous.push(current_node);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    if distance.contains_key(&dest_node) {
+        let mut path = vec![dest_node];
+        collect_paths(&predecessors, start_node, &mut path, &mut paths);
+    }
+    paths
+}
+
+fn collect_paths(
+    predecessors: &HashMap<NodeId, Vec<NodeId>>,
+    start_node: NodeId,
+    path: &mut Vec<NodeId>,
+    paths: &mut Vec<Vec<NodeId>>,
+) {
+    let current_node = *path.last().unwrap();
+    if current_node == start_node {
+        paths.push(path.iter().rev().copied().collect());
+        return;
+    }
+
+    for previous in predecessors.get(&current_node).into_iter().flatten() {
+        path.push(*previous);
+        collect_paths(predecessors, start_node, path, paths);
+        path.pop();
+    }
+}
+
+/// Same answer as `shortest_path_to` but the search runs from
+/// both ends at once and stops when the two searches meet in
+/// the middle.
+///
+/// A one-sided BFS up to distance `d` visits roughly `b^d`
+/// nodes (`b` being the average number of neighbours), two
+/// searches up to `d / 2` only visit about `2 * b^(d / 2)`.
+/// The backward search follows the edges in reverse so this
+/// works for directed graphs too.
+///
+/// We always grow the smaller of the two frontiers by one whole
+/// level. Once a level produces a meeting point we pick the
+/// best of all the meeting points found in that level.
+pub fn bidirectional_shortest_path(
+    graph: &Graph,
+    start_node: NodeId,
+    dest_node: NodeId,
+) -> Option<Vec<NodeId>> {
+    if start_node == dest_node {
+        return Some(vec![start_node]);
+    }
+
+    // node -> (distance, predecessor) on each side
+    let mut forward = HashMap::new();
+    forward.insert(start_node, (0, None));
+    let mut backward = HashMap::new();
+    backward.insert(dest_node, (0, None));
+    let mut forward_frontier = vec![start_node];
+    let mut backward_frontier = vec![dest_node];
+
+    while !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+        let meeting = if forward_frontier.len() <= backward_frontier.len() {
+            let (frontier, meeting) = expand_level(
+                &graph.adjacency_list,
+                &forward_frontier,
+                &mut forward,
+                &backward,
+            );
+            forward_frontier = frontier;
+            meeting
+        } else {
+            let (frontier, meeting) = expand_level(
+                &graph.reverse_adjacency_list,
+                &backward_frontier,
+                &mut backward,
+                &forward,
+            );
+            backward_frontier = frontier;
+            // flip it around so it reads start --> dest
+            meeting.map(|(backward_node, forward_node)| {
+                (forward_node, backward_node)
+            })
+        };
+
+        if let Some((forward_node, backward_node)) = meeting {
+            let mut path = walk_back(&forward, forward_node);
+            path.reverse();
+            if forward_node != backward_node {
+                path.extend(walk_back(&backward, backward_node));
+            }
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Grows one side of the bidirectional search by a level.
+/// Returns the next frontier and the best `(this side node,
+/// other side node)` edge joining the two searches, if any.
+fn expand_level(
+    adjacency_list: &HashMap<NodeId, Vec<NodeId>>,
+    frontier: &[NodeId],
+    this_side: &mut HashMap<NodeId, (usize, Option<NodeId>)>,
+    other_side: &HashMap<NodeId, (usize, Option<NodeId>)>,
+) -> (Vec<NodeId>, Option<(NodeId, NodeId)>) {
+    let mut next_frontier = Vec::new();
+    let mut best: Option<(usize, NodeId, NodeId)> = None;
+
+    for current_node in frontier {
+        let distance = this_side[current_node].0;
+        for neighbour in adjacency_list.get(current_node).into_iter().flatten()
+        {
+            if let Some((other_distance, _)) = other_side.get(neighbour) {
+                let total = distance + 1 + other_distance;
+                if best.is_none_or(|(best_total, _, _)| total < best_total) {
+                    best = Some((total, *current_node, *neighbour));
+                }
+            }
+            if !this_side.contains_key(neighbour) {
+                this_side
+                    .insert(*neighbour, (distance + 1, Some(*current_node)));
+                next_frontier.push(*neighbour);
+            }
+        }
+    }
+
+    (
+        next_frontier,
+        best.map(|(_, this_node, other_node)| (this_node, other_node)),
+    )
+}
+
+/// Follows the predecessors from `node` back to where that
+/// side of the search started
+fn walk_back(
+    side: &HashMap<NodeId, (usize, Option<NodeId>)>,
+    node: NodeId,
+) -> Vec<NodeId> {
+    let mut path = vec![node];
+    let mut current_node = node;
+    while let Some((_, Some(previous))) = side.get(&current_node) {
+        path.push(*previous);
+        current_node = *previous;
+    }
+    path
 }
 
 #[cfg(test)]
@@ -119,12 +367,12 @@ mod tests {
         // 3
         //
         // 4 <--> 5
-        let result = shortest_path(graph.clone(), 0, 3);
-        assert_eq!(result, 2);
-        let result = shortest_path(graph.clone(), 0, 4);
-        assert_eq!(result, -1);
-        let result = shortest_path(graph, 3, 1);
-        assert_eq!(result, 2);
+        let result = shortest_path(&graph, 0, 3);
+        assert_eq!(result, Some(2));
+        let result = shortest_path(&graph, 0, 4);
+        assert_eq!(result, None);
+        let result = shortest_path(&graph, 3, 1);
+        assert_eq!(result, Some(2));
     }
 
     #[test]
@@ -155,17 +403,17 @@ mod tests {
         // 4      5
         //
         // 6 <--> 7
-        let result = shortest_path(graph.clone(), 0, 5);
-        assert_eq!(result, 3);
-        let result = shortest_path(graph.clone(), 0, 4);
-        assert_eq!(result, 2);
-        let result = shortest_path(graph.clone(), 2, 5);
-        assert_eq!(result, 4);
-        let result = shortest_path(graph.clone(), 3, 1);
-        assert_eq!(result, 1);
+        let result = shortest_path(&graph, 0, 5);
+        assert_eq!(result, Some(3));
+        let result = shortest_path(&graph, 0, 4);
+        assert_eq!(result, Some(2));
+        let result = shortest_path(&graph, 2, 5);
+        assert_eq!(result, Some(4));
+        let result = shortest_path(&graph, 3, 1);
+        assert_eq!(result, Some(1));
 
-        let result = shortest_path(graph, 3, 7);
-        assert_eq!(result, -1);
+        let result = shortest_path(&graph, 3, 7);
+        assert_eq!(result, None);
     }
 
     #[test]
@@ -193,15 +441,218 @@ mod tests {
         // v      v
         // 4 <--> 5
         //
-        let result = shortest_path(graph.clone(), 0, 5);
-        assert_eq!(result, 3);
-        let result = shortest_path(graph.clone(), 0, 4);
-        assert_eq!(result, 2);
-        let result = shortest_path(graph.clone(), 2, 5);
-        assert_eq!(result, 2);
-        let result = shortest_path(graph.clone(), 1, 5);
-        assert_eq!(result, 2);
-        let result = shortest_path(graph.clone(), 4, 5);
-        assert_eq!(result, 1);
+        let result = shortest_path(&graph, 0, 5);
+        assert_eq!(result, Some(3));
+        let result = shortest_path(&graph, 0, 4);
+        assert_eq!(result, Some(2));
+        let result = shortest_path(&graph, 2, 5);
+        assert_eq!(result, Some(2));
+        let result = shortest_path(&graph, 1, 5);
+        assert_eq!(result, Some(2));
+        let result = shortest_path(&graph, 4, 5);
+        assert_eq!(result, Some(1));
+    }
+
+    #[test]
+    fn test_shortest_path_to() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(4, 5);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      |
+        // 2 <----
+        // ^
+        // |
+        // v
+        // 3
+        //
+        // 4 <--> 5
+        let result = shortest_path_to(&graph, 0, 3);
+        assert_eq!(result, Some(vec![0, 2, 3]));
+        let result = shortest_path_to(&graph, 3, 3);
+        assert_eq!(result, Some(vec![3]));
+        let result = shortest_path_to(&graph, 0, 4);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_shortest_path_to_any() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(2, 4);
+        graph.add_undirected_edge(3, 5);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      v
+        // 2      3
+        // ^      ^
+        // |      |
+        // v      v
+        // 4      5
+        let result = shortest_path_to_any(&graph, 0, &[5, 4]);
+        assert_eq!(result, Some(vec![0, 2, 4]));
+        let result = shortest_path_to_any(&graph, 5, &[4, 0, 1]);
+        assert_eq!(result, Some(vec![5, 3, 1]));
+        let result = shortest_path_to_any(&graph, 0, &[]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_node(6);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(2, 4);
+        graph.add_undirected_edge(3, 5);
+        graph.add_undirected_edge(4, 5);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      v
+        // 2      3
+        // ^      ^
+        // |      |
+        // v      v
+        // 4 <--> 5
+        //
+        // 6
+        let mut result = all_shortest_paths(&graph, 0, 5);
+        result.sort();
+        assert_eq!(result, vec![vec![0, 1, 3, 5], vec![0, 2, 4, 5]]);
+        let result = all_shortest_paths(&graph, 0, 3);
+        assert_eq!(result, vec![vec![0, 1, 3]]);
+        let result = all_shortest_paths(&graph, 4, 4);
+        assert_eq!(result, vec![vec![4]]);
+        let result = all_shortest_paths(&graph, 0, 6);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_all_shortest_paths_parallel_edges() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(1, 2);
+        // 0 <==> 1 <==> 2
+        let result = all_shortest_paths(&graph, 0, 1);
+        assert_eq!(result, vec![vec![0, 1]]);
+        let result = all_shortest_paths(&graph, 0, 2);
+        assert_eq!(result, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_dangling_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_directed_edge(0, 1);
+        // 0 ---> 1
+        // 1 never got its own entry
+        let result = shortest_path_to(&graph, 0, 1);
+        assert_eq!(result, Some(vec![0, 1]));
+        let result = shortest_path(&graph, 1, 0);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_bidirectional_shortest_path() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_node(6);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(1, 3);
+        graph.add_undirected_edge(2, 4);
+        graph.add_undirected_edge(3, 5);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 6);
+        // 0 <--> 1
+        // ^      ^
+        // |      |
+        // v      v
+        // 2      3
+        // ^      ^
+        // |      |
+        // v      v
+        // 4 <--> 5 <--> 6
+        for start in 0..7 {
+            for dest in 0..7 {
+                let expected = shortest_path_to(&graph, start, dest);
+                let result = bidirectional_shortest_path(&graph, start, dest);
+                assert_eq!(
+                    result.as_ref().map(|p| p.len()),
+                    expected.map(|p| p.len())
+                );
+                let path = result.unwrap();
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&dest));
+                for edge in path.windows(2) {
+                    assert!(graph.neighbours(&edge[0]).contains(&edge[1]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_shortest_path_directed() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 0);
+        graph.add_directed_edge(0, 4);
+        // 0 ---> 1
+        // ^      |
+        // |      v
+        // 3 <--- 2
+        //
+        // 0 ---> 4
+        let result = bidirectional_shortest_path(&graph, 1, 0);
+        assert_eq!(result, Some(vec![1, 2, 3, 0]));
+        let result = bidirectional_shortest_path(&graph, 3, 4);
+        assert_eq!(result, Some(vec![3, 0, 4]));
+        let result = bidirectional_shortest_path(&graph, 4, 0);
+        assert_eq!(result, None);
     }
 }
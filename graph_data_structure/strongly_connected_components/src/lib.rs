@@ -0,0 +1,368 @@
+use std::collections::{HashMap, HashSet};
+
+pub use topological_sort::{Graph, NodeId};
+
+/// Tarjan's algorithm.
+///
+/// A single depth-first search where every node gets an
+/// `index` (the order we discovered it in) and a `low_link`
+/// (the smallest index reachable from it through the nodes
+/// still on the stack). A node whose `low_link` is its own
+/// index is the root of a component: everything above it on
+/// the stack belongs to that component.
+///
+/// Components come out sinks first, i.e. in reverse
+/// topological order. The nodes of each component are
+/// sorted.
+pub fn tarjan(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let mut state = Tarjan {
+        graph,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for node in graph.nodes() {
+        if !state.index.contains_key(&node) {
+            state.strong_connect(node);
+        }
+    }
+    state.components
+}
+
+struct Tarjan<'a> {
+    graph: &'a Graph,
+    index: HashMap<NodeId, usize>,
+    low_link: HashMap<NodeId, usize>,
+    stack: Vec<NodeId>,
+    on_stack: HashSet<NodeId>,
+    components: Vec<Vec<NodeId>>,
+}
+
+impl Tarjan<'_> {
+    fn strong_connect(&mut self, root: NodeId) {
+        let graph = self.graph;
+        // the path of the DFS, each node with how many of its
+        // neighbours we've been through. A long chain of
+        // dependencies would overflow the call stack if each
+        // node took a recursive call.
+        let mut path = vec![(root, 0)];
+        self.discover(root);
+
+        while let Some((current_node, next)) = path.last().copied() {
+            if let Some(neighbour) = graph.neighbours(current_node).get(next) {
+                path.last_mut().unwrap().1 += 1;
+                if !self.index.contains_key(neighbour) {
+                    self.discover(*neighbour);
+                    path.push((*neighbour, 0));
+                } else if self.on_stack.contains(neighbour) {
+                    let low = self.low_link[&current_node]
+                        .min(self.index[neighbour]);
+                    self.low_link.insert(current_node, low);
+                }
+                continue;
+            }
+
+            // done with `current_node`, the node we reached it
+            // from can reach whatever it can
+            path.pop();
+            if let Some((parent, _)) = path.last() {
+                let low =
+                    self.low_link[parent].min(self.low_link[&current_node]);
+                self.low_link.insert(*parent, low);
+            }
+
+            if self.low_link[&current_node] == self.index[&current_node] {
+                let mut component = Vec::new();
+                while let Some(node) = self.stack.pop() {
+                    self.on_stack.remove(&node);
+                    component.push(node);
+                    if node == current_node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn discover(&mut self, node: NodeId) {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.low_link.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+    }
+}
+
+/// Kosaraju's algorithm.
+///
+/// 1. DFS the graph and note the order nodes finish in.
+/// 2. DFS the reversed graph, starting from the nodes that
+///    finished last. Each of these searches stays within one
+///    component, since the edges that used to leave it now
+///    point into components that are already done.
+///
+/// Components come out in topological order. The nodes of
+/// each component are sorted.
+pub fn kosaraju(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let mut visited = HashSet::new();
+    let mut finished = Vec::new();
+    for node in graph.nodes() {
+        finish_order(graph, node, &mut visited, &mut finished);
+    }
+
+    let mut reverse = Graph::new();
+    for node in graph.nodes() {
+        reverse.add_node(node);
+        for neighbour in graph.neighbours(node) {
+            reverse.add_directed_edge(*neighbour, node);
+        }
+    }
+
+    let mut assigned = HashSet::new();
+    let mut components = Vec::new();
+    for node in finished.into_iter().rev() {
+        if assigned.contains(&node) {
+            continue;
+        }
+        let mut component = Vec::new();
+        collect(&reverse, node, &mut assigned, &mut component);
+        component.sort_unstable();
+        components.push(component);
+    }
+    components
+}
+
+// Both searches keep their own stack for the same reason as
+// `Tarjan::strong_connect`
+fn finish_order(
+    graph: &Graph,
+    start_node: NodeId,
+    visited: &mut HashSet<NodeId>,
+    finished: &mut Vec<NodeId>,
+) {
+    if !visited.insert(start_node) {
+        return;
+    }
+    // each node with how many of its neighbours we've been
+    // through
+    let mut path = vec![(start_node, 0)];
+    while let Some((current_node, next)) = path.last().copied() {
+        match graph.neighbours(current_node).get(next) {
+            Some(neighbour) => {
+                path.last_mut().unwrap().1 += 1;
+                if visited.insert(*neighbour) {
+                    path.push((*neighbour, 0));
+                }
+            }
+            None => {
+                path.pop();
+                finished.push(current_node);
+            }
+        }
+    }
+}
+
+fn collect(
+    graph: &Graph,
+    start_node: NodeId,
+    assigned: &mut HashSet<NodeId>,
+    component: &mut Vec<NodeId>,
+) {
+    if !assigned.insert(start_node) {
+        return;
+    }
+    let mut stack = vec![start_node];
+    while let Some(current_node) = stack.pop() {
+        component.push(current_node);
+        for neighbour in graph.neighbours(current_node) {
+            if assigned.insert(*neighbour) {
+                stack.push(*neighbour);
+            }
+        }
+    }
+}
+
+/// Maps every node to the index of its component in
+/// `components`
+pub fn component_membership(
+    components: &[Vec<NodeId>],
+) -> HashMap<NodeId, usize> {
+    let mut membership = HashMap::new();
+    for (i, component) in components.iter().enumerate() {
+        for node in component {
+            membership.insert(*node, i);
+        }
+    }
+    membership
+}
+
+/// The graph with every strongly connected component
+/// squashed into a single node.
+#[derive(Clone, Debug)]
+pub struct Condensation {
+    /// The components in topological order, component `i`
+    /// is node `i` of `graph`
+    pub components: Vec<Vec<NodeId>>,
+    /// Which component each node of the original graph is in
+    pub membership: HashMap<NodeId, usize>,
+    /// There is an edge `i --> j` if some node of component
+    /// `i` has an edge to some node of component `j`. This is
+    /// always a DAG, so it can go straight into a topological
+    /// sort or a longest path computation.
+    pub graph: Graph,
+}
+
+/// Builds the condensation of `graph`, using Tarjan's
+/// algorithm to find the components
+pub fn condensation(graph: &Graph) -> Condensation {
+    let mut components = tarjan(graph);
+    components.reverse();
+    let membership = component_membership(&components);
+
+    let mut dag = Graph::new();
+    let mut edges = HashSet::new();
+    for i in 0..components.len() {
+        dag.add_node(i);
+    }
+    for node in graph.nodes() {
+        let from = membership[&node];
+        for neighbour in graph.neighbours(node) {
+            let to = membership[neighbour];
+            if from != to && edges.insert((from, to)) {
+                dag.add_directed_edge(from, to);
+            }
+        }
+    }
+
+    Condensation { components, membership, graph: dag }
+}
+
+/// The same DAG of components, for `longest_path`: the
+/// longest chain of components one has to go through.
+impl From<&Condensation> for longest_path::Graph {
+    fn from(condensation: &Condensation) -> Self {
+        let mut graph = longest_path::Graph::new();
+        for from in condensation.graph.nodes() {
+            graph.add_node(from);
+            for to in condensation.graph.neighbours(from) {
+                graph.add_directed_edge(from, *to);
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 4);
+        graph.add_directed_edge(4, 5);
+        graph.add_directed_edge(5, 3);
+        graph.add_directed_edge(6, 5);
+        graph.add_directed_edge(6, 7);
+        graph.add_directed_edge(7, 6);
+        graph.add_directed_edge(1, 8);
+        // 0 ---> 1 ---> 8
+        // ^      |
+        // |      v
+        //  ----- 2 ---> 3 ---> 4
+        //               ^      |
+        //               |      v
+        //                ----- 5 <--- 6 <--> 7
+        graph
+    }
+
+    #[test]
+    fn test_tarjan() {
+        let components = tarjan(&example_graph());
+        assert_eq!(
+            components,
+            vec![vec![3, 4, 5], vec![8], vec![0, 1, 2], vec![6, 7]]
+        );
+    }
+
+    #[test]
+    fn test_kosaraju() {
+        let components = kosaraju(&example_graph());
+        assert_eq!(
+            components,
+            vec![vec![6, 7], vec![0, 1, 2], vec![8], vec![3, 4, 5]]
+        );
+    }
+
+    #[test]
+    fn test_same_components() {
+        let graph = example_graph();
+        let mut a = tarjan(&graph);
+        let mut b = kosaraju(&graph);
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+
+        let membership = component_membership(&a);
+        assert_eq!(membership[&0], membership[&2]);
+        assert_eq!(membership[&4], membership[&5]);
+        assert_ne!(membership[&2], membership[&3]);
+    }
+
+    #[test]
+    fn test_acyclic_graph() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_node(3);
+        let components = tarjan(&graph);
+        assert_eq!(components, vec![vec![2], vec![1], vec![0], vec![3]]);
+    }
+
+    #[test]
+    fn test_condensation() {
+        let condensation = condensation(&example_graph());
+        assert_eq!(condensation.components.len(), 4);
+        assert_eq!(condensation.membership.len(), 9);
+
+        let order =
+            topological_sort::topological_sort_dfs(&condensation.graph)
+                .unwrap();
+        assert_eq!(order.len(), 4);
+
+        // components come in topological order
+        for from in condensation.graph.nodes() {
+            for to in condensation.graph.neighbours(from) {
+                assert!(from < *to);
+            }
+        }
+
+        // longest chain of components, {0, 1, 2} --> {3, 4, 5}
+        // or {0, 1, 2} --> {8} or {6, 7} --> {3, 4, 5}
+        let graph = longest_path::Graph::from(&condensation);
+        assert_eq!(longest_path::longest_path(graph), Ok(1));
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // deep enough to overflow the call stack if each node
+        // took a recursive call
+        let mut graph = Graph::new();
+        for (u, v) in graph_generators::path(100_001, true).edges {
+            graph.add_directed_edge(u, v);
+        }
+        assert_eq!(tarjan(&graph).len(), 100_001);
+        assert_eq!(kosaraju(&graph).len(), 100_001);
+
+        graph.add_directed_edge(100_000, 0);
+        assert_eq!(tarjan(&graph), vec![(0..=100_000).collect::<Vec<_>>()]);
+        assert_eq!(kosaraju(&graph), vec![(0..=100_000).collect::<Vec<_>>()]);
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+pub type NodeId = usize;
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self { adjacency_list: HashMap::new() }
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+    }
+
+    // Use this method to add directed edge u --> v
+    pub fn add_directed_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.add_node(v);
+    }
+
+    /// All the nodes of the graph in ascending order
+    pub fn nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> =
+            self.adjacency_list.keys().copied().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.adjacency_list.get(&node).map_or(&[], |n| n.as_slice())
+    }
+
+    fn in_degrees(&self) -> HashMap<NodeId, usize> {
+        let mut in_degree: HashMap<NodeId, usize> =
+            self.adjacency_list.keys().map(|n| (*n, 0)).collect();
+        for neighbours in self.adjacency_list.values() {
+            for neighbour in neighbours {
+                *in_degree.entry(*neighbour).or_default() += 1;
+            }
+        }
+        in_degree
+    }
+}
+
+/// The nodes of a cycle in the order the edges run, the
+/// last node having an edge back to the first one.
+/// This is what keeps a graph from being sorted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<NodeId>);
+
+/// Kahn's algorithm.
+///
+/// Keep a queue of the nodes nothing points to anymore
+/// (in-degree 0). Taking a node off the queue "removes"
+/// its outgoing edges, which may bring some of its
+/// neighbours down to in-degree 0 in turn. If we run out of
+/// nodes before every node is placed the rest sit on or
+/// behind a cycle.
+pub fn kahn(graph: &Graph) -> Result<Vec<NodeId>, Cycle> {
+    let mut in_degree = graph.in_degrees();
+    let mut queue: VecDeque<NodeId> =
+        graph.nodes().into_iter().filter(|n| in_degree[n] == 0).collect();
+    let mut order = Vec::new();
+
+    while let Some(current_node) = queue.pop_front() {
+        order.push(current_node);
+        for neighbour in graph.neighbours(current_node) {
+            let degree = in_degree.get_mut(neighbour).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+
+    check_complete(graph, order)
+}
+
+/// Kahn's algorithm with a min-heap instead of a queue, so
+/// that out of all the nodes that are ready we always pick
+/// the smallest one. The result is the lexicographically
+/// smallest topological order.
+pub fn lexicographic_topological_sort(
+    graph: &Graph,
+) -> Result<Vec<NodeId>, Cycle> {
+    let mut in_degree = graph.in_degrees();
+    let mut heap: BinaryHeap<Reverse<NodeId>> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(node, _)| Reverse(*node))
+        .collect();
+    let mut order = Vec::new();
+
+    while let Some(Reverse(current_node)) = heap.pop() {
+        order.push(current_node);
+        for neighbour in graph.neighbours(current_node) {
+            let degree = in_degree.get_mut(neighbour).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                heap.push(Reverse(*neighbour));
+            }
+        }
+    }
+
+    check_complete(graph, order)
+}
+
+fn check_complete(
+    graph: &Graph,
+    order: Vec<NodeId>,
+) -> Result<Vec<NodeId>, Cycle> {
+    if order.len() == graph.adjacency_list.len() {
+        Ok(order)
+    } else {
+        // Kahn's algorithm only tells us that a cycle
+        // exists so we go and find one with a DFS
+        Err(find_cycle(graph).expect("unsorted nodes imply a cycle"))
+    }
+}
+
+/// Depth-first search based topological sort.
+///
+/// A node is finished once everything reachable from it is
+/// finished, so the reverse of the finishing order puts
+/// every node before the nodes it points to. Uses the same
+/// white-grey-black colouring as `has_cycle`: running into
+/// a grey node means we've found a cycle.
+pub fn topological_sort_dfs(graph: &Graph) -> Result<Vec<NodeId>, Cycle> {
+    let nodes = graph.nodes();
+    let index: HashMap<NodeId, usize> =
+        nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+    let mut colour = vec![Colour::White; nodes.len()];
+    // the grey nodes, i.e. the path from the DFS root, each
+    // with how many of its neighbours we've been through. An
+    // explicit stack as a long chain would overflow the call
+    // stack.
+    let mut stack: Vec<(NodeId, usize)> = Vec::new();
+    let mut finished = Vec::new();
+
+    for start_node in nodes {
+        if colour[index[&start_node]] != Colour::White {
+            continue;
+        }
+        colour[index[&start_node]] = Colour::Grey;
+        stack.push((start_node, 0));
+
+        while let Some((current_node, next)) = stack.last().copied() {
+            let Some(neighbour) = graph.neighbours(current_node).get(next)
+            else {
+                stack.pop();
+                colour[index[&current_node]] = Colour::Black;
+                finished.push(current_node);
+                continue;
+            };
+            stack.last_mut().unwrap().1 += 1;
+            match colour[index[neighbour]] {
+                Colour::White => {
+                    colour[index[neighbour]] = Colour::Grey;
+                    stack.push((*neighbour, 0));
+                }
+                // coming back to a node on the path closes a
+                // cycle, only now is it worth looking for it
+                Colour::Grey => {
+                    let i = stack
+                        .iter()
+                        .position(|(node, _)| node == neighbour)
+                        .unwrap();
+                    let cycle = stack[i..].iter().map(|(node, _)| *node);
+                    return Err(Cycle(cycle.collect()));
+                }
+                Colour::Black => {}
+            }
+        }
+    }
+
+    finished.reverse();
+    Ok(finished)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Colour {
+    White, // not visited yet
+    Grey,  // on the path from the DFS root
+    Black, // finished
+}
+
+/// Returns a cycle of the graph if there is one
+pub fn find_cycle(graph: &Graph) -> Option<Cycle> {
+    topological_sort_dfs(graph).err()
+}
+
+/// Takes in a number of courses and a list of prerequisites
+/// like `prereqs_possible` does, with `[A, B]` meaning that
+/// course A must be taken before course B. Returns the
+/// lexicographically smallest order to take the courses in,
+/// or the cycle of prerequisites that makes it impossible.
+pub fn course_order(
+    num_courses: usize,
+    prereqs: &[[usize; 2]],
+) -> Result<Vec<usize>, Cycle> {
+    let mut graph = Graph::new();
+    for course in 0..num_courses {
+        graph.add_node(course);
+    }
+    for [course_a, course_b] in prereqs {
+        graph.add_directed_edge(*course_a, *course_b);
+    }
+    lexicographic_topological_sort(&graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_topological(graph: &Graph, order: &[NodeId]) {
+        assert_eq!(order.len(), graph.nodes().len());
+        let position: HashMap<NodeId, usize> =
+            order.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        for node in graph.nodes() {
+            for neighbour in graph.neighbours(node) {
+                assert!(position[&node] < position[neighbour]);
+            }
+        }
+    }
+
+    fn assert_cycle(graph: &Graph, cycle: &Cycle) {
+        assert!(!cycle.0.is_empty());
+        for (i, node) in cycle.0.iter().enumerate() {
+            let next = cycle.0[(i + 1) % cycle.0.len()];
+            assert!(graph.neighbours(*node).contains(&next));
+        }
+    }
+
+    #[test]
+    fn test_add_edge() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(0, 2);
+        graph.add_directed_edge(1, 2);
+        assert_eq!(graph.nodes(), vec![0, 1, 2]);
+        assert_eq!(graph.neighbours(0), &[1, 2]);
+        assert_eq!(graph.neighbours(2), &[] as &[NodeId]);
+        assert_eq!(graph.neighbours(7), &[] as &[NodeId]);
+    }
+
+    #[test]
+    fn test_sort_00() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_node(5);
+        graph.add_directed_edge(5, 2);
+        graph.add_directed_edge(5, 0);
+        graph.add_directed_edge(4, 0);
+        graph.add_directed_edge(4, 1);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 1);
+        // 5 ---> 2 ---> 3 ---> 1
+        // |                    ^
+        // v                    |
+        // 0 <--- 4 ------------
+        let order = kahn(&graph).unwrap();
+        assert_topological(&graph, &order);
+        assert_eq!(order, vec![4, 5, 2, 0, 3, 1]);
+        let order = topological_sort_dfs(&graph).unwrap();
+        assert_topological(&graph, &order);
+        let order = lexicographic_topological_sort(&graph).unwrap();
+        assert_eq!(order, vec![4, 5, 0, 2, 3, 1]);
+        assert_eq!(find_cycle(&graph), None);
+    }
+
+    #[test]
+    fn test_lexicographic() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_directed_edge(3, 0);
+        graph.add_directed_edge(1, 0);
+        // 3 ---> 0 <--- 1
+        //
+        // 2
+        let order = lexicographic_topological_sort(&graph).unwrap();
+        assert_eq!(order, vec![1, 2, 3, 0]);
+        // plain Kahn puts every ready node in the queue up
+        // front so 0 only comes out after all of them
+        let order = kahn(&graph).unwrap();
+        assert_eq!(order, vec![1, 2, 3, 0]);
+
+        let mut graph = Graph::new();
+        graph.add_directed_edge(2, 0);
+        graph.add_directed_edge(1, 3);
+        // 2 ---> 0    1 ---> 3
+        let order = lexicographic_topological_sort(&graph).unwrap();
+        assert_eq!(order, vec![1, 2, 0, 3]);
+        let order = kahn(&graph).unwrap();
+        assert_eq!(order, vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 2);
+        graph.add_directed_edge(2, 3);
+        graph.add_directed_edge(3, 1);
+        graph.add_directed_edge(3, 4);
+        // 0 ---> 1 --> 2
+        //        ^     |
+        //        |     |
+        //        3 <-- v
+        //        |
+        //        v
+        //        4
+        for result in [
+            kahn(&graph),
+            topological_sort_dfs(&graph),
+            lexicographic_topological_sort(&graph),
+        ] {
+            let cycle = result.unwrap_err();
+            assert_cycle(&graph, &cycle);
+            assert_eq!(cycle, Cycle(vec![1, 2, 3]));
+        }
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let mut graph = Graph::new();
+        graph.add_directed_edge(0, 1);
+        graph.add_directed_edge(1, 1);
+        assert_eq!(kahn(&graph), Err(Cycle(vec![1])));
+    }
+
+    #[test]
+    fn test_course_order() {
+        let prereqs = [[0, 1], [2, 3], [0, 2], [1, 3], [4, 5]];
+        let order = course_order(6, &prereqs);
+        assert_eq!(order, Ok(vec![0, 1, 2, 3, 4, 5]));
+
+        let prereqs = [[1, 0], [0, 6], [2, 0], [0, 5], [3, 7], [4, 3]];
+        let order = course_order(8, &prereqs);
+        assert_eq!(order, Ok(vec![1, 2, 0, 4, 3, 5, 6, 7]));
+
+        let prereqs = [[1, 0], [0, 6], [2, 0], [0, 5], [3, 7], [7, 4], [4, 3]];
+        let cycle = course_order(8, &prereqs).unwrap_err();
+        assert_eq!(cycle, Cycle(vec![3, 7, 4]));
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // deep enough to overflow the call stack if each node
+        // took a recursive call
+        let mut graph = Graph::new();
+        for node in 0..200_000 {
+            graph.add_directed_edge(node, node + 1);
+        }
+        let order = topological_sort_dfs(&graph).unwrap();
+        assert!(order.iter().copied().eq(0..=200_000));
+
+        graph.add_directed_edge(200_000, 0);
+        let cycle = find_cycle(&graph).unwrap();
+        assert_eq!(cycle.0.len(), 200_001);
+    }
+}
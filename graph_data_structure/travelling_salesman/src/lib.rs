@@ -0,0 +1,522 @@
+//! Hamiltonian paths and cycles, and the travelling salesman
+//! problem.
+//!
+//! The exact solvers look at every subset of the nodes, so
+//! they are for small graphs only. The weighted functions take
+//! a distance matrix, `distance[u][v]` being the weight of the
+//! edge u --> v or `None` if there is no such edge, and name
+//! the nodes by their index in it.
+pub use compressed_graph::{Adjacency, CsrGraph, Graph, NodeId};
+
+pub type Weight = i64;
+
+/// The exact solvers keep `n * 2^n` weights around, about
+/// 160 MB at this size, and refuse anything bigger
+pub const MAX_EXACT_NODES: usize = 20;
+
+/// The nodes in the order they are visited and the total
+/// weight of the edges between them. For a cycle the edge from
+/// the last node back to the first is included in the length
+/// but the first node isn't repeated at the end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tour {
+    pub nodes: Vec<usize>,
+    pub length: Weight,
+}
+
+// what a memo entry can hold besides an actual weight
+const UNKNOWN: Weight = Weight::MIN;
+const IMPOSSIBLE: Weight = Weight::MAX;
+
+/// Held-Karp: the cheapest way to go through a set of nodes
+/// and end up at `last` only depends on the set and `last`,
+/// not on the order the nodes were visited in. So there are
+/// only `n * 2^n` subproblems, each one remembered in `memo`
+/// with the set as a bitmask.
+struct HeldKarp<'a> {
+    distance: &'a [Vec<Option<Weight>>],
+    // a cycle always starts from node 0, a path from
+    // anywhere
+    cycle: bool,
+    memo: Vec<Weight>,
+}
+
+impl<'a> HeldKarp<'a> {
+    fn new(distance: &'a [Vec<Option<Weight>>], cycle: bool) -> Self {
+        let n = distance.len();
+        assert!(
+            n <= MAX_EXACT_NODES,
+            "{} nodes are too many for an exact search",
+            n
+        );
+        assert!(distance.iter().all(|row| row.len() == n));
+        // every set of a cycle holds node 0, so its bit can
+        // be dropped from the index
+        let sets = 1 << (n - cycle as usize);
+        Self { distance, cycle, memo: vec![UNKNOWN; sets * n] }
+    }
+
+    fn index(&self, visited: usize, last: usize) -> usize {
+        (visited >> self.cycle as usize) * self.distance.len() + last
+    }
+
+    /// Length of the cheapest path through exactly the nodes
+    /// in `visited`, ending at `last`
+    fn cost(&mut self, visited: usize, last: usize) -> Weight {
+        // a cycle has to leave node 0 first and can't come
+        // back to it until the very end. These sets don't get
+        // an entry of their own in `memo` either.
+        if self.cycle && (visited & 1 == 0 || (last == 0 && visited != 1)) {
+            return IMPOSSIBLE;
+        }
+        let key = self.index(visited, last);
+        // If the calculated value is in cache
+        // then return it
+        if self.memo[key] != UNKNOWN {
+            return self.memo[key];
+        }
+
+        let rest = visited & !(1 << last);
+        let result = if rest == 0 {
+            0
+        } else {
+            let mut best = IMPOSSIBLE;
+            for previous in 0..self.distance.len() {
+                if rest & (1 << previous) == 0 {
+                    continue;
+                }
+                let Some(weight) = self.distance[previous][last] else {
+                    continue;
+                };
+                let cost = self.cost(rest, previous);
+                if cost != IMPOSSIBLE {
+                    best = best.min(cost + weight);
+                }
+            }
+            best
+        };
+
+        self.memo[key] = result;
+        result
+    }
+
+    /// The nodes of the path `cost(visited, last)` found,
+    /// rebuilt by looking for a previous node that accounts
+    /// for the cost at every step
+    fn path(&mut self, mut visited: usize, mut last: usize) -> Vec<usize> {
+        let mut path = vec![last];
+        loop {
+            let rest = visited & !(1 << last);
+            if rest == 0 {
+                break;
+            }
+            let cost = self.cost(visited, last);
+            let previous = (0..self.distance.len())
+                .find(|previous| {
+                    rest & (1 << previous) != 0
+                        && self.distance[*previous][last].is_some_and(|w| {
+                            let before = self.cost(rest, *previous);
+                            before != IMPOSSIBLE && before + w == cost
+                        })
+                })
+                .unwrap();
+            path.push(previous);
+            visited = rest;
+            last = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// The shortest cycle through every node exactly once,
+/// starting from node 0. `None` if there is no such cycle,
+/// or no nodes. A single node makes a cycle on its own.
+/// O(n^2 * 2^n) time.
+///
+/// Panics with more than `MAX_EXACT_NODES` nodes or if the
+/// matrix isn't square.
+pub fn held_karp(distance: &[Vec<Option<Weight>>]) -> Option<Tour> {
+    let n = distance.len();
+    if n <= 1 {
+        return (n == 1).then(|| Tour { nodes: vec![0], length: 0 });
+    }
+    let mut search = HeldKarp::new(distance, true);
+    let all = (1 << n) - 1;
+    let (length, last) = (1..n)
+        .filter_map(|last| {
+            let back = distance[last][0]?;
+            let cost = search.cost(all, last);
+            (cost != IMPOSSIBLE).then(|| (cost + back, last))
+        })
+        .min()?;
+    Some(Tour { nodes: search.path(all, last), length })
+}
+
+/// The shortest path through every node exactly once, from
+/// whichever node to whichever other one is cheapest. `None`
+/// if there is no such path, or no nodes.
+///
+/// Panics with more than `MAX_EXACT_NODES` nodes or if the
+/// matrix isn't square.
+pub fn held_karp_path(distance: &[Vec<Option<Weight>>]) -> Option<Tour> {
+    let n = distance.len();
+    let mut search = HeldKarp::new(distance, false);
+    let all = (1 << n) - 1;
+    let (length, last) = (0..n)
+        .map(|last| (search.cost(all, last), last))
+        .filter(|(cost, _)| *cost != IMPOSSIBLE)
+        .min()?;
+    Some(Tour { nodes: search.path(all, last), length })
+}
+
+/// The graph as a distance matrix with every edge weighing 1,
+/// along with the node each row stands for
+fn unit_distances<G: Adjacency>(
+    graph: &G,
+) -> (Vec<NodeId>, Vec<Vec<Option<Weight>>>) {
+    let nodes = graph.nodes();
+    let mut index = vec![0; graph.node_bound()];
+    for (i, node) in nodes.iter().enumerate() {
+        index[*node] = i;
+    }
+    let mut distance = vec![vec![None; nodes.len()]; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for neighbour in graph.neighbours(*node) {
+            distance[i][index[*neighbour]] = Some(1);
+        }
+    }
+    (nodes, distance)
+}
+
+/// A path through every node of the graph exactly once.
+/// Panics with more than `MAX_EXACT_NODES` nodes.
+pub fn hamiltonian_path<G: Adjacency>(graph: &G) -> Option<Vec<NodeId>> {
+    let (nodes, distance) = unit_distances(graph);
+    let tour = held_karp_path(&distance)?;
+    Some(tour.nodes.into_iter().map(|i| nodes[i]).collect())
+}
+
+/// A cycle through every node of the graph exactly once,
+/// starting from the smallest node. In an undirected graph
+/// two nodes and an edge between them make a cycle, going
+/// there and back the same way. Panics with more than
+/// `MAX_EXACT_NODES` nodes.
+pub fn hamiltonian_cycle<G: Adjacency>(graph: &G) -> Option<Vec<NodeId>> {
+    let (nodes, distance) = unit_distances(graph);
+    let tour = held_karp(&distance)?;
+    Some(tour.nodes.into_iter().map(|i| nodes[i]).collect())
+}
+
+fn tour_length(
+    distance: &[Vec<Option<Weight>>],
+    nodes: &[usize],
+) -> Option<Weight> {
+    (0..nodes.len())
+        .map(|i| distance[nodes[i]][nodes[(i + 1) % nodes.len()]])
+        .sum()
+}
+
+/// Nearest neighbour heuristic: from `start`, keep going to
+/// the closest node not visited yet. Quick, but usually
+/// around a quarter longer than the best cycle. `None` if it
+/// runs into a node with no way on, or no way back to
+/// `start` at the end.
+pub fn nearest_neighbour(
+    distance: &[Vec<Option<Weight>>],
+    start: usize,
+) -> Option<Tour> {
+    let n = distance.len();
+    let mut visited = vec![false; n];
+    let mut nodes = vec![start];
+    visited[start] = true;
+    let mut current = start;
+    for _ in 1..n {
+        let (_, next) = (0..n)
+            .filter(|next| !visited[*next])
+            .filter_map(|next| Some((distance[current][next]?, next)))
+            .min()?;
+        visited[next] = true;
+        nodes.push(next);
+        current = next;
+    }
+    let length = tour_length(distance, &nodes)?;
+    Some(Tour { nodes, length })
+}
+
+/// 2-opt: takes two edges a --> b and c --> d of the cycle
+/// and reconnects them as a --> c and b --> d, reversing the
+/// stretch from b to c, whenever that makes the cycle
+/// shorter. Repeats until no such pair is left. This undoes
+/// every place where the cycle crosses itself.
+///
+/// Reversing the stretch turns its edges around as well, so
+/// with distances that differ between the two ways, or edges
+/// missing one way, the whole cycle is priced again for every
+/// move. That's O(n) per move rather than O(1).
+pub fn two_opt(distance: &[Vec<Option<Weight>>], tour: &Tour) -> Tour {
+    let mut nodes = tour.nodes.clone();
+    let mut length = tour.length;
+    let n = nodes.len();
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n.saturating_sub(2) {
+            for j in i + 2..n {
+                // these two edges share node a
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                nodes[i + 1..=j].reverse();
+                match tour_length(distance, &nodes) {
+                    Some(new_length) if new_length < length => {
+                        length = new_length;
+                        improved = true;
+                    }
+                    _ => nodes[i + 1..=j].reverse(),
+                }
+            }
+        }
+    }
+    Tour { nodes, length }
+}
+
+/// A good cycle for graphs too big for `held_karp`: the
+/// shortest of the nearest neighbour cycles from every node,
+/// improved with 2-opt. Starts from node 0 like `held_karp`.
+/// O(n^3) for the nearest neighbour cycles, then 2-opt as
+/// long as it keeps finding something.
+pub fn approximate_tour(distance: &[Vec<Option<Weight>>]) -> Option<Tour> {
+    let best = (0..distance.len())
+        .filter_map(|start| nearest_neighbour(distance, start))
+        .min_by_key(|tour| tour.length)?;
+    let mut tour = two_opt(distance, &best);
+    let zero = tour.nodes.iter().position(|node| *node == 0).unwrap();
+    tour.nodes.rotate_left(zero);
+    Some(tour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::{complete, cycle, grid, star, Rng};
+
+    /// Random points on a 1000 x 1000 square, with the rounded
+    /// distances between them
+    fn random_points(n: usize, seed: u64) -> Vec<Vec<Option<Weight>>> {
+        let mut rng = Rng::new(seed);
+        let mut next = || rng.below(1000) as f64;
+        let points: Vec<(f64, f64)> =
+            (0..n).map(|_| (next(), next())).collect();
+        points
+            .iter()
+            .map(|(x1, y1)| {
+                points
+                    .iter()
+                    .map(|(x2, y2)| {
+                        let distance =
+                            ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt();
+                        Some(distance.round() as Weight)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Tries every order of the nodes after 0
+    fn brute_force(distance: &[Vec<Option<Weight>>]) -> Option<Weight> {
+        fn permute(
+            distance: &[Vec<Option<Weight>>],
+            nodes: &mut Vec<usize>,
+            k: usize,
+            best: &mut Option<Weight>,
+        ) {
+            if k == nodes.len() {
+                if let Some(length) = tour_length(distance, nodes) {
+                    *best = Some(best.map_or(length, |best| best.min(length)));
+                }
+                return;
+            }
+            for i in k..nodes.len() {
+                nodes.swap(k, i);
+                permute(distance, nodes, k + 1, best);
+                nodes.swap(k, i);
+            }
+        }
+        let mut nodes: Vec<usize> = (0..distance.len()).collect();
+        let mut best = None;
+        permute(distance, &mut nodes, 1, &mut best);
+        best
+    }
+
+    fn assert_visits_all(tour: &Tour, n: usize) {
+        let mut nodes = tour.nodes.clone();
+        nodes.sort_unstable();
+        assert_eq!(nodes, (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_held_karp() {
+        let distance = vec![
+            vec![Some(0), Some(10), Some(15), Some(20)],
+            vec![Some(10), Some(0), Some(35), Some(25)],
+            vec![Some(15), Some(35), Some(0), Some(30)],
+            vec![Some(20), Some(25), Some(30), Some(0)],
+        ];
+        let tour = held_karp(&distance).unwrap();
+        // 0 --> 1 --> 3 --> 2 the other way round is just as
+        // short
+        assert_eq!(tour, Tour { nodes: vec![0, 2, 3, 1], length: 80 });
+        let path = held_karp_path(&distance).unwrap();
+        // 2 --> 0 --> 1 --> 3 or the other way round
+        assert_eq!(path.length, 50);
+        assert!(path.nodes == [2, 0, 1, 3] || path.nodes == [3, 1, 0, 2]);
+
+        // one-way streets
+        let distance = vec![
+            vec![None, Some(1), Some(9)],
+            vec![Some(9), None, Some(1)],
+            vec![Some(1), None, None],
+        ];
+        let tour = held_karp(&distance).unwrap();
+        assert_eq!(tour, Tour { nodes: vec![0, 1, 2], length: 3 });
+        assert_eq!(held_karp(&[]), None);
+        assert_eq!(held_karp(&[vec![None]]).unwrap().nodes, vec![0]);
+    }
+
+    #[test]
+    fn test_held_karp_against_brute_force() {
+        for seed in 0..20 {
+            let mut distance = random_points(8, seed);
+            assert_eq!(
+                held_karp(&distance).map(|tour| tour.length),
+                brute_force(&distance)
+            );
+            // take some edges away so that there isn't always
+            // a cycle
+            for (i, row) in distance.iter_mut().enumerate() {
+                for (j, weight) in row.iter_mut().enumerate() {
+                    if (i * 7 + j * 3 + seed as usize).is_multiple_of(4) {
+                        *weight = None;
+                    }
+                }
+            }
+            let tour = held_karp(&distance);
+            assert_eq!(
+                tour.as_ref().map(|tour| tour.length),
+                brute_force(&distance)
+            );
+            if let Some(tour) = tour {
+                assert_visits_all(&tour, 8);
+                assert_eq!(
+                    tour_length(&distance, &tour.nodes),
+                    Some(tour.length)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hamiltonian() {
+        // the Petersen graph: a path through every node but
+        // no cycle
+        let mut graph = Graph::new();
+        for i in 0..5 {
+            graph.add_undirected_edge(i, (i + 1) % 5);
+            graph.add_undirected_edge(i, i + 5);
+            graph.add_undirected_edge(i + 5, (i + 2) % 5 + 5);
+        }
+        let path = hamiltonian_path(&graph).unwrap();
+        assert_eq!(path.len(), 10);
+        for pair in path.windows(2) {
+            assert!(graph.has_edge(pair[0], pair[1]));
+        }
+        assert_eq!(hamiltonian_cycle(&graph), None);
+
+        assert_eq!(
+            hamiltonian_cycle(&cycle(6, true).build::<Graph>()),
+            Some(vec![0, 1, 2, 3, 4, 5])
+        );
+        assert!(
+            hamiltonian_cycle(&complete(7, false).build::<Graph>()).is_some()
+        );
+        // a grid with an odd number of nodes alternates
+        // between 5 of one colour and 4 of the other
+        assert!(hamiltonian_cycle(&grid(3, 3).build::<Graph>()).is_none());
+        assert!(hamiltonian_path(&grid(3, 3).build::<Graph>()).is_some());
+        assert!(hamiltonian_cycle(&grid(2, 3).build::<Graph>()).is_some());
+        assert!(hamiltonian_path(&star(4).build::<Graph>()).is_none());
+    }
+
+    #[test]
+    fn test_heuristics() {
+        for seed in 0..10 {
+            let distance = random_points(10, seed);
+            let best = held_karp(&distance).unwrap().length;
+            let greedy = (0..10)
+                .filter_map(|start| nearest_neighbour(&distance, start))
+                .map(|tour| tour.length)
+                .min()
+                .unwrap();
+            let tour = approximate_tour(&distance).unwrap();
+            assert_eq!(tour.nodes[0], 0);
+            assert_visits_all(&tour, 10);
+            assert_eq!(tour_length(&distance, &tour.nodes), Some(tour.length));
+            assert!(best <= tour.length);
+            assert!(tour.length <= greedy);
+        }
+
+        let distance = random_points(150, 42);
+        let greedy = nearest_neighbour(&distance, 0).unwrap();
+        let improved = two_opt(&distance, &greedy);
+        assert_visits_all(&improved, 150);
+        assert_eq!(
+            tour_length(&distance, &improved.nodes),
+            Some(improved.length)
+        );
+        assert!(improved.length < greedy.length);
+
+        let distance = vec![vec![None, Some(1)], vec![None, None]];
+        assert_eq!(nearest_neighbour(&distance, 0), None);
+        assert_eq!(approximate_tour(&distance), None);
+    }
+
+    #[test]
+    fn test_two_opt_one_way_distances() {
+        // reversing a stretch of this cycle turns its edges
+        // around, and they cost something else the other way
+        let distance: Vec<Vec<Option<Weight>>> = [
+            [None, Some(7), Some(6), Some(2), Some(30)],
+            [Some(36), None, Some(44), Some(35), Some(40)],
+            [Some(25), Some(27), None, Some(32), Some(36)],
+            [Some(8), Some(48), Some(13), None, Some(38)],
+            [Some(30), Some(10), Some(11), Some(31), None],
+        ]
+        .iter()
+        .map(|row| row.to_vec())
+        .collect();
+        let tour = approximate_tour(&distance).unwrap();
+        assert_visits_all(&tour, 5);
+        assert_eq!(tour_length(&distance, &tour.nodes), Some(tour.length));
+        assert!(held_karp(&distance).unwrap().length <= tour.length);
+        for start in 0..5 {
+            let greedy = nearest_neighbour(&distance, start).unwrap();
+            let improved = two_opt(&distance, &greedy);
+            assert!(improved.length <= greedy.length);
+            assert_eq!(
+                tour_length(&distance, &improved.nodes),
+                Some(improved.length)
+            );
+        }
+
+        // the reversed edges may not exist at all
+        let distance = vec![
+            vec![None, Some(1), Some(5), None],
+            vec![None, None, Some(1), Some(5)],
+            vec![Some(5), None, None, Some(1)],
+            vec![Some(1), Some(5), None, None],
+        ];
+        let tour = Tour { nodes: vec![0, 1, 2, 3], length: 4 };
+        assert_eq!(two_opt(&distance, &tour), tour);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+pub type NodeId = usize;
+
+/// Disjoint-set union (union-find).
+///
+/// Every set is a tree and is named after the root of that
+/// tree. Two tricks keep the trees flat so that `find` and
+/// `union` run in (almost) constant time:
+///
+/// - path compression: `find` points every node it walks
+///   past straight at the root
+/// - union by size: the smaller tree always goes under the
+///   root of the larger one
+#[derive(Clone, Debug, Default)]
+pub struct DisjointSet {
+    parent: HashMap<NodeId, NodeId>,
+    // only kept up to date for roots
+    size: HashMap<NodeId, usize>,
+    count: usize,
+    largest: usize,
+}
+
+impl DisjointSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `node` as a set of its own. Returns `false` if
+    /// it was already there.
+    pub fn make_set(&mut self, node: NodeId) -> bool {
+        if self.parent.contains_key(&node) {
+            return false;
+        }
+        self.parent.insert(node, node);
+        self.size.insert(node, 1);
+        self.count += 1;
+        self.largest = self.largest.max(1);
+        true
+    }
+
+    /// The root of the set `node` belongs to, `None` if
+    /// `node` was never added.
+    pub fn find(&mut self, node: NodeId) -> Option<NodeId> {
+        let mut root = *self.parent.get(&node)?;
+        while self.parent[&root] != root {
+            root = self.parent[&root];
+        }
+
+        // second pass to point everything at the root
+        let mut current_node = node;
+        while current_node != root {
+            let next = self.parent.insert(current_node, root).unwrap();
+            current_node = next;
+        }
+
+        Some(root)
+    }
+
+    /// Merges the sets of `a` and `b`, adding either of them
+    /// first if needed. Returns `false` if they were already
+    /// in the same set.
+    pub fn union(&mut self, a: NodeId, b: NodeId) -> bool {
+        self.make_set(a);
+        self.make_set(b);
+        let (mut root_a, mut root_b) =
+            (self.find(a).unwrap(), self.find(b).unwrap());
+        if root_a == root_b {
+            return false;
+        }
+
+        if self.size[&root_a] < self.size[&root_b] {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parent.insert(root_b, root_a);
+        let size = self.size[&root_a] + self.size.remove(&root_b).unwrap();
+        self.size.insert(root_a, size);
+        self.count -= 1;
+        self.largest = self.largest.max(size);
+        true
+    }
+
+    pub fn connected(&mut self, a: NodeId, b: NodeId) -> bool {
+        match (self.find(a), self.find(b)) {
+            (Some(root_a), Some(root_b)) => root_a == root_b,
+            _ => false,
+        }
+    }
+
+    /// Number of nodes in the set `node` belongs to
+    pub fn set_size(&mut self, node: NodeId) -> Option<usize> {
+        let root = self.find(node)?;
+        Some(self.size[&root])
+    }
+
+    /// Number of sets
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Size of the largest set. Sets only ever grow so we can
+    /// keep track of this as we go.
+    pub fn largest(&self) -> usize {
+        self.largest
+    }
+
+    /// Number of nodes over all the sets
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+}
+
+/// An undirected graph that keeps its connected components
+/// up to date as nodes and edges are added, instead of
+/// running a full DFS like `connected_components_count` and
+/// `largest_component` do every time they are asked.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency_list: HashMap<NodeId, Vec<NodeId>>,
+    components: DisjointSet,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node_id: NodeId) {
+        self.adjacency_list.entry(node_id).or_default();
+        self.components.make_set(node_id);
+    }
+
+    // add a bi-directional edge u <--> v
+    pub fn add_undirected_edge(&mut self, u: NodeId, v: NodeId) {
+        self.adjacency_list.entry(u).or_default().push(v);
+        self.adjacency_list.entry(v).or_default().push(u);
+        self.components.union(u, v);
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.adjacency_list.get(&node).map_or(&[], |n| n.as_slice())
+    }
+
+    pub fn connected_components_count(&self) -> usize {
+        self.components.count()
+    }
+
+    pub fn largest_component(&self) -> usize {
+        self.components.largest()
+    }
+
+    /// Whether there is a path between `u` and `v`
+    pub fn connected(&mut self, u: NodeId, v: NodeId) -> bool {
+        self.components.connected(u, v)
+    }
+
+    /// Size of the connected component `node` is in
+    pub fn component_size(&mut self, node: NodeId) -> Option<usize> {
+        self.components.set_size(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph_generators::Rng;
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut set = DisjointSet::new();
+        assert!(set.is_empty());
+        assert_eq!(set.find(0), None);
+        assert!(set.make_set(0));
+        assert!(!set.make_set(0));
+        assert!(set.union(1, 2));
+        assert!(set.union(2, 3));
+        assert!(!set.union(1, 3));
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.count(), 2);
+        assert_eq!(set.largest(), 3);
+        assert!(set.connected(1, 3));
+        assert!(!set.connected(0, 3));
+        assert!(!set.connected(0, 42));
+        assert_eq!(set.set_size(3), Some(3));
+        assert_eq!(set.set_size(0), Some(1));
+        assert_eq!(set.set_size(42), None);
+        assert_eq!(set.find(1), set.find(3));
+    }
+
+    #[test]
+    fn test_path_compression() {
+        let mut set = DisjointSet::new();
+        for node in 0..100 {
+            set.union(node, node + 1);
+        }
+        let root = set.find(0).unwrap();
+        for node in 0..=100 {
+            assert_eq!(set.parent[&node], root);
+        }
+        assert_eq!(set.set_size(50), Some(101));
+    }
+
+    #[test]
+    fn test_incremental_graph() {
+        let mut graph = Graph::new();
+        graph.add_node(0);
+        graph.add_node(1);
+        graph.add_node(2);
+        graph.add_node(3);
+        graph.add_node(4);
+        assert_eq!(graph.connected_components_count(), 5);
+        assert_eq!(graph.largest_component(), 1);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        assert_eq!(graph.connected_components_count(), 3);
+        assert_eq!(graph.largest_component(), 3);
+        graph.add_undirected_edge(2, 0);
+        assert_eq!(graph.connected_components_count(), 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        // 0 <--> 1 <--> 2
+        // ^             ^
+        // |             |
+        //  -------------
+        //
+        // 3 <--> 4 <--> 5
+        assert_eq!(graph.connected_components_count(), 2);
+        assert_eq!(graph.largest_component(), 3);
+        assert!(graph.connected(0, 2));
+        assert!(!graph.connected(0, 5));
+        assert!(graph.connected(3, 3));
+        assert!(!graph.connected(7, 7));
+        assert_eq!(graph.component_size(5), Some(3));
+        assert_eq!(graph.neighbours(4), &[3, 5]);
+    }
+
+    #[test]
+    fn test_matches_dfs_counts() {
+        let mut rng = Rng::new(12345);
+        let nodes = 60;
+        let mut graph = Graph::new();
+        let mut dfs_count = connected_components_count::Graph::new();
+        let mut dfs_largest = largest_component::Graph::new();
+        for node in 0..nodes {
+            graph.add_node(node);
+            dfs_count.add_node(node);
+            dfs_largest.add_node(node);
+        }
+
+        for _ in 0..80 {
+            let (u, v) = (rng.below(nodes), rng.below(nodes));
+            graph.add_undirected_edge(u, v);
+            dfs_count.add_undirected_edge(u, v);
+            dfs_largest.add_undirected_edge(u, v);
+
+            let count = connected_components_count::connected_components_count(
+                dfs_count.clone(),
+            );
+            let largest =
+                largest_component::largest_component(dfs_largest.clone());
+            assert_eq!(graph.connected_components_count(), count as usize);
+            assert_eq!(graph.largest_component(), largest as usize);
+        }
+    }
+}